@@ -0,0 +1,6 @@
+-- Add migration script here
+alter table offered_post
+  add status text not null default 'unknown';
+
+create index offered_post_chat_id_status_index
+    on offered_post (chat_id, status);
@@ -0,0 +1,3 @@
+-- Add migration script here
+alter table offered_post
+  add channel_message_id integer;
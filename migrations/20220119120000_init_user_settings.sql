@@ -0,0 +1,12 @@
+-- Add migration script here
+create table user_settings
+(
+    chat_id integer not null
+        constraint user_settings_pk primary key,
+    credit  boolean not null default 0
+);
+
+create unique index user_settings_chat_id_index on user_settings (chat_id);
+
+alter table offered_post
+  add author text;
@@ -0,0 +1,9 @@
+-- Add migration script here
+alter table user_settings
+  add anonymous boolean not null default 0;
+
+alter table user_settings
+  add anon_token text;
+
+alter table offered_post
+  add is_anonymous boolean not null default 0;
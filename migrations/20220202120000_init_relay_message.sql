@@ -0,0 +1,17 @@
+-- Add migration script here
+create table relay_message
+(
+    id               integer not null
+        constraint relay_message_pk
+            primary key autoincrement,
+    admin_chat_id    integer not null,
+    admin_message_id integer not null,
+    user_chat_id     integer not null,
+    user_message_id  integer not null
+);
+
+create index relay_message_admin_index
+    on relay_message (admin_chat_id, admin_message_id);
+
+create index relay_message_user_index
+    on relay_message (user_chat_id, user_message_id);
@@ -0,0 +1,9 @@
+-- Add migration script here
+create table vote
+(
+    admin_chat_id         integer not null,
+    admin_chat_message_id integer not null,
+    voter_id              integer not null,
+    is_accept             boolean not null,
+    constraint vote_pk primary key (admin_chat_id, admin_chat_message_id, voter_id)
+);
@@ -0,0 +1,9 @@
+-- Add migration script here
+alter table offered_post
+  add created_at integer not null default 0;
+
+alter table offered_post
+  add kind text;
+
+create index offered_post_admin_chat_id_status_index
+    on offered_post (admin_chat_id, status);
@@ -0,0 +1,15 @@
+-- Add migration script here
+create table webhook_event
+(
+    id              integer not null
+        constraint webhook_event_pk
+            primary key autoincrement,
+    url             text    not null,
+    event           text    not null,
+    payload         text    not null,
+    attempts        integer not null default 0,
+    next_attempt_at integer not null
+);
+
+create index webhook_event_next_attempt_index
+    on webhook_event (next_attempt_at);
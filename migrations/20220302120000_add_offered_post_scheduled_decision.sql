@@ -0,0 +1,9 @@
+-- Add migration script here
+alter table offered_post
+  add scheduled_decision text;
+
+alter table offered_post
+  add scheduled_by text;
+
+alter table offered_post
+  add scheduled_at integer;
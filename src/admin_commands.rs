@@ -1,20 +1,33 @@
 use std::borrow::Cow;
 use std::env;
 
+use chrono::Utc;
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde_json::json;
 use teloxide::prelude::*;
 use teloxide::types::ParseMode::MarkdownV2;
-use teloxide::types::{ChatId, InputFile};
+use teloxide::types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup, InputFile};
 
+use crate::data::model::offered_post::OfferedPostStatus;
 use crate::data::model::pic::Pic;
+use crate::data::model::relay_message::RelayMessage;
 use crate::data::repo::offered_post_repo::OfferedPostRepo;
 use crate::data::repo::pic_repo::PicRepo;
 use crate::data::repo::ban_repo::BanRepo;
+use crate::data::repo::relay_message_repo::RelayMessageRepo;
+use crate::data::repo::stats_repo::StatsRepo;
+use crate::data::repo::webhook_event_repo::WebhookEventRepo;
+use crate::data::repo::Repos;
 use crate::utils::document_utils::download_animate_vec;
 use crate::utils::error_utils::HandlerError;
+use crate::utils::link_utils::channel_post_link;
 use crate::utils::option_utils::unwrap_send_error;
+use crate::utils::request_utils::SendRetry;
+use crate::utils::time_utils::format_age;
 use crate::utils::version::VERSION_STRING;
+use crate::webhooks::{emit, WebhookEventKind};
+use crate::CHANNEL_ID;
 
 static HELP_CMD: &str = "/help";
 static VERSION_CMD: &str = "/version";
@@ -26,6 +39,12 @@ static MSG_CMD: &str = "/msg";
 static BAN_CMD: &str = "/ban";
 static UNBAN_CMD: &str = "/unban";
 static GET_BAN_LIST_CMD: &str = "/banlist";
+static UNPUBLISH_CMD: &str = "/unpublish";
+static PENDING_CMD: &str = "/pending";
+
+pub static PENDING_PAGE_CALLBACK: &str = "pending-page";
+
+const PENDING_PAGE_SIZE: i64 = 10;
 
 static MSG_PREFIX_KEY: &str = "MSG_PREFIX";
 
@@ -40,10 +59,17 @@ lazy_static! {
 pub async fn exec_command(
     text: &str,
     cx: &UpdateWithCx<Bot, Message>,
-    pic_repo: &PicRepo,
-    offered_post_repo: &OfferedPostRepo,
-    ban_repo: &BanRepo,
+    repos: &Repos,
 ) -> Result<(), HandlerError> {
+    let Repos {
+        offered_post_repo,
+        pic_repo,
+        stats_repo,
+        ban_repo,
+        relay_message_repo,
+        webhook_event_repo,
+        ..
+    } = repos;
     if text.starts_with(VERSION_CMD) {
         version(cx).await?
     } else if text.starts_with(GET_BAN_LIST_CMD) {
@@ -53,25 +79,90 @@ pub async fn exec_command(
     } else if text.starts_with(LIST_CMD) {
         list(cx, pic_repo).await?
     } else if text.starts_with(ADD_CMD) {
-        add(&cx, pic_repo, &text).await?
+        add(&cx, pic_repo, &text, webhook_event_repo).await?
     } else if text.starts_with(GET_CMD) {
         get(cx, pic_repo, text).await?
     } else if text.starts_with(DELETE_CMD) {
-        delete(cx, pic_repo, text).await?
+        delete(cx, pic_repo, text, webhook_event_repo).await?
     } else if text.starts_with(MSG_CMD) {
-        send_msg(cx, offered_post_repo, text).await?
+        send_msg(cx, offered_post_repo, relay_message_repo, text).await?
     } else if text.starts_with(BAN_CMD) {
-        ban(cx, offered_post_repo, ban_repo).await?
+        ban(cx, offered_post_repo, ban_repo, webhook_event_repo).await?
     } else if text.starts_with(UNBAN_CMD) {
-        unban(cx, offered_post_repo, ban_repo).await?
+        unban(cx, offered_post_repo, ban_repo, webhook_event_repo).await?
+    } else if text.starts_with(UNPUBLISH_CMD) {
+        unpublish(cx, offered_post_repo, stats_repo).await?
+    } else if text.starts_with(PENDING_CMD) {
+        pending(cx, offered_post_repo).await?
     }
     Ok(())
 }
 
+pub async fn set_ban(
+    ban_repo: &BanRepo,
+    webhook_event_repo: &WebhookEventRepo,
+    chat_id: i64,
+    user_name: String,
+    date: String,
+    is_ban: bool,
+) -> Result<(), sqlx::Error> {
+    ban_repo
+        .create(chat_id, user_name, date.to_string())
+        .await?;
+    ban_repo.update(chat_id, date, is_ban).await?;
+    emit(
+        webhook_event_repo,
+        if is_ban {
+            WebhookEventKind::UserBanned
+        } else {
+            WebhookEventKind::UserUnbanned
+        },
+        json!({ "chat_id": chat_id }),
+    )
+    .await;
+    Ok(())
+}
+
+pub async fn save_pic(
+    pic_repo: &PicRepo,
+    webhook_event_repo: &WebhookEventRepo,
+    pic: Pic,
+) -> Result<(), sqlx::Error> {
+    let file_name = pic.file_name.to_string();
+    let for_accept = pic.for_accept;
+    pic_repo.save_pic(pic).await?;
+    emit(
+        webhook_event_repo,
+        WebhookEventKind::PicAdded,
+        json!({ "file_name": file_name, "for_accept": for_accept }),
+    )
+    .await;
+    Ok(())
+}
+
+pub async fn remove_pic(
+    pic_repo: &PicRepo,
+    webhook_event_repo: &WebhookEventRepo,
+    file_name: String,
+    for_accept: bool,
+) -> Result<(), sqlx::Error> {
+    pic_repo
+        .delete_pic(file_name.to_string(), for_accept)
+        .await?;
+    emit(
+        webhook_event_repo,
+        WebhookEventKind::PicRemoved,
+        json!({ "file_name": file_name, "for_accept": for_accept }),
+    )
+    .await;
+    Ok(())
+}
+
 async fn delete(
     cx: &UpdateWithCx<Bot, Message>,
     pic_repo: &PicRepo,
     text: &str,
+    webhook_event_repo: &WebhookEventRepo,
 ) -> Result<(), HandlerError> {
     let captures = unwrap_send_error(
         RM_REGEX.captures(text),
@@ -82,12 +173,19 @@ async fn delete(
 
     let for_accept = captures.get(1).unwrap().as_str() == "A";
     let file_name = captures.get(2).unwrap().as_str();
-    if let Err(_) = pic_repo.delete_pic(file_name.to_string(), for_accept).await {
+    if let Err(_) = remove_pic(
+        pic_repo,
+        webhook_event_repo,
+        file_name.to_string(),
+        for_accept,
+    )
+    .await
+    {
         cx.reply_to("Image with this filename and mark does not exist.")
-            .send()
+            .send_retry()
             .await?;
     } else {
-        cx.reply_to("Delete successful.").send().await?;
+        cx.reply_to("Delete successful.").send_retry().await?;
     }
     Ok(())
 }
@@ -121,7 +219,7 @@ async fn get(
         file_name: pic.file_name,
         data: Cow::from(pic.data),
     })
-    .send()
+    .send_retry()
     .await?;
     Ok(())
 }
@@ -130,6 +228,7 @@ async fn add(
     cx: &&UpdateWithCx<Bot, Message>,
     pic_repo: &PicRepo,
     text: &&str,
+    webhook_event_repo: &WebhookEventRepo,
 ) -> Result<(), HandlerError> {
     let captures = unwrap_send_error(
         ADD_REGEX.captures(&text),
@@ -154,16 +253,19 @@ async fn add(
     let file_name = animation.file_name.as_ref().unwrap_or(&default_file_name);
     if let Ok(_) = pic_repo.get_pic(file_name.to_string(), for_accept).await {
         cx.reply_to("Pic with this name and mark already exists.")
-            .send()
+            .send_retry()
             .await?;
     } else {
-        if let Err(_) = pic_repo
-            .save_pic(Pic::new(file_name.to_string(), for_accept, data.clone()))
-            .await
+        if let Err(_) = save_pic(
+            pic_repo,
+            webhook_event_repo,
+            Pic::new(file_name.to_string(), for_accept, data.clone()),
+        )
+        .await
         {
-            cx.reply_to("Add error. Smoke logs.").send().await?;
+            cx.reply_to("Add error. Smoke logs.").send_retry().await?;
         } else {
-            cx.reply_to("Add successful.").send().await?;
+            cx.reply_to("Add successful.").send_retry().await?;
         }
     }
     Ok(())
@@ -183,13 +285,15 @@ async fn list(cx: &UpdateWithCx<Bot, Message>, pic_repo: &PicRepo) -> Result<(),
             .collect::<Vec<String>>()
             .join("\n");
         if _list.is_empty() {
-            cx.reply_to("Pic list is empty ").send().await?;
+            cx.reply_to("Pic list is empty ").send_retry().await?;
         } else {
-            cx.reply_to(format!("Pic list:\n{}", _list)).send().await?;
+            cx.reply_to(format!("Pic list:\n{}", _list))
+                .send_retry()
+                .await?;
         }
     } else {
         cx.reply_to("An error occurred when requesting Pics list. Smoke logs.")
-            .send()
+            .send_retry()
             .await?;
     }
     Ok(())
@@ -203,21 +307,26 @@ async fn help(cx: &UpdateWithCx<Bot, Message>) -> Result<(), HandlerError> {
              - /get {A/D} <file_name (from /list)> - get pic.\n\
              - /add {A/D} - add pic.\n\
              - /rm {A/D} <file_name (from /list)> - remove pic.\n\
-             - /banlist - get all bans (for change ban state - reply with /ban or /unban).",
+             - /banlist - get all bans (for change ban state - reply with /ban or /unban).\n\
+             - /msg <text> - reply to a suggestion to message its author. \
+             Replies of the author show up here, reply to them without a command to answer.\n\
+             - /unpublish - reply to an accepted suggestion to delete its channel post.\n\
+             - /pending - list undecided suggestions.",
     )
-    .send()
+    .send_retry()
     .await?;
     Ok(())
 }
 
 async fn version(cx: &UpdateWithCx<Bot, Message>) -> Result<(), HandlerError> {
-    cx.reply_to(VERSION_STRING).send().await?;
+    cx.reply_to(VERSION_STRING).send_retry().await?;
     Ok(())
 }
 
 async fn send_msg(
     cx: &UpdateWithCx<Bot, Message>,
     offered_post_repo: &OfferedPostRepo,
+    relay_message_repo: &RelayMessageRepo,
     text: &str,
 ) -> Result<(), HandlerError> {
     let captures = unwrap_send_error(
@@ -239,15 +348,24 @@ async fn send_msg(
     .await?;
     let msg = captures.get(1).unwrap().as_str();
 
-    cx.requester
+    let sent = cx
+        .requester
         .send_message(
             ChatId::Id(post.chat_id),
             format!("{}{}", MSG_PREFIX.as_str(), msg),
         )
         .reply_to_message_id(post.message_id)
         .parse_mode(MarkdownV2)
-        .send()
+        .send_retry()
         .await?;
+    let _ = relay_message_repo
+        .save_relay_message(RelayMessage::new(
+            cx.update.chat_id(),
+            cx.update.id,
+            post.chat_id,
+            sent.id,
+        ))
+        .await;
 
     Ok(())
 }
@@ -256,6 +374,7 @@ async fn ban(
     cx: &UpdateWithCx<Bot, Message>,
     offered_post_repo: &OfferedPostRepo,
     ban_repo: &BanRepo,
+    webhook_event_repo: &WebhookEventRepo,
 ) -> Result<(), HandlerError> {
     let message =
         unwrap_send_error(cx.update.reply_to_message(), cx, "Reply message not found.").await?;
@@ -270,16 +389,22 @@ async fn ban(
     .await?;
 
     unwrap_send_error(
-        ban_repo
-            .update(post.chat_id, message.date.to_string(), true)
-            .await
-            .ok(),
+        set_ban(
+            ban_repo,
+            webhook_event_repo,
+            post.chat_id,
+            post.chat_id.to_string(),
+            message.date.to_string(),
+            true,
+        )
+        .await
+        .ok(),
         cx,
         "Offered post not found.",
     )
     .await?;
 
-    cx.reply_to("🤕 OK").send().await?;
+    cx.reply_to("🤕 OK").send_retry().await?;
 
     Ok(())
 }
@@ -288,6 +413,7 @@ async fn unban(
     cx: &UpdateWithCx<Bot, Message>,
     offered_post_repo: &OfferedPostRepo,
     ban_repo: &BanRepo,
+    webhook_event_repo: &WebhookEventRepo,
 ) -> Result<(), HandlerError> {
     let message =
         unwrap_send_error(cx.update.reply_to_message(), cx, "Reply message not found.").await?;
@@ -302,16 +428,22 @@ async fn unban(
     .await?;
 
     unwrap_send_error(
-        ban_repo
-            .update(post.chat_id, message.date.to_string(), false)
-            .await
-            .ok(),
+        set_ban(
+            ban_repo,
+            webhook_event_repo,
+            post.chat_id,
+            post.chat_id.to_string(),
+            message.date.to_string(),
+            false,
+        )
+        .await
+        .ok(),
         cx,
         "Offered post not found.",
     )
     .await?;
 
-    cx.reply_to("🥴 OK").send().await?;
+    cx.reply_to("🥴 OK").send_retry().await?;
 
     Ok(())
 }
@@ -329,14 +461,177 @@ async fn banlist(
             .collect::<Vec<String>>()
             .join("\n");
         if _list.is_empty() {
-            cx.reply_to("Ban list is empty").send().await?;
+            cx.reply_to("Ban list is empty").send_retry().await?;
         } else {
-            cx.reply_to(format!("Ban list:\n{}", _list)).send().await?;
+            cx.reply_to(format!("Ban list:\n{}", _list))
+                .send_retry()
+                .await?;
         }
     } else {
         cx.reply_to("An error occurred when requesting Bans list. Smoke logs.")
-            .send()
+            .send_retry()
             .await?;
     }
     Ok(())
 }
+
+async fn unpublish(
+    cx: &UpdateWithCx<Bot, Message>,
+    offered_post_repo: &OfferedPostRepo,
+    stats_repo: &StatsRepo,
+) -> Result<(), HandlerError> {
+    let message =
+        unwrap_send_error(cx.update.reply_to_message(), cx, "Reply message not found.").await?;
+    let post = unwrap_send_error(
+        offered_post_repo
+            .get_offered_post(message.chat_id(), message.id)
+            .await
+            .ok(),
+        cx,
+        "Offered post not found.",
+    )
+    .await?;
+    let channel_message_id = unwrap_send_error(
+        post.channel_message_id
+            .filter(|_| post.status == OfferedPostStatus::Accepted),
+        cx,
+        "This suggestion is not published.",
+    )
+    .await?;
+
+    cx.requester
+        .delete_message(CHANNEL_ID.to_string(), channel_message_id)
+        .send_retry()
+        .await?;
+    offered_post_repo
+        .update_status(
+            post.admin_chat_id,
+            post.admin_chat_message_id,
+            OfferedPostStatus::Unpublished,
+        )
+        .await?;
+    stats_repo.decrement_accepted(post.chat_id).await?;
+
+    cx.reply_to("🗑 OK").send_retry().await?;
+
+    Ok(())
+}
+
+pub async fn pending_callback(
+    cx: &UpdateWithCx<Bot, CallbackQuery>,
+    data: &str,
+    offered_post_repo: &OfferedPostRepo,
+) -> Result<(), HandlerError> {
+    let message = cx
+        .update
+        .message
+        .as_ref()
+        .ok_or(HandlerError::missing("Message not found"))?;
+    let page = data
+        .trim_start_matches(PENDING_PAGE_CALLBACK)
+        .trim_start_matches(':')
+        .parse::<i64>()
+        .unwrap_or(0);
+    let (text, keyboard) = build_pending_page(offered_post_repo, message.chat_id(), page).await?;
+    cx.requester
+        .edit_message_text(message.chat_id(), message.id, text)
+        .reply_markup(keyboard)
+        .send_retry()
+        .await?;
+    cx.requester
+        .answer_callback_query(cx.update.id.to_string())
+        .send_retry()
+        .await?;
+    Ok(())
+}
+
+async fn pending(
+    cx: &UpdateWithCx<Bot, Message>,
+    offered_post_repo: &OfferedPostRepo,
+) -> Result<(), HandlerError> {
+    let (text, keyboard) = build_pending_page(offered_post_repo, cx.update.chat_id(), 0).await?;
+    cx.reply_to(text)
+        .reply_markup(keyboard)
+        .send_retry()
+        .await?;
+    Ok(())
+}
+
+async fn build_pending_page(
+    offered_post_repo: &OfferedPostRepo,
+    admin_chat_id: i64,
+    page: i64,
+) -> Result<(String, InlineKeyboardMarkup), HandlerError> {
+    let count = offered_post_repo.count_pending(admin_chat_id).await?;
+    let pages = ((count + PENDING_PAGE_SIZE - 1) / PENDING_PAGE_SIZE).max(1);
+    let page = page.max(0).min(pages - 1);
+    let posts = offered_post_repo
+        .get_pending_page(admin_chat_id, PENDING_PAGE_SIZE, page * PENDING_PAGE_SIZE)
+        .await?;
+    if posts.is_empty() {
+        return Ok((
+            "No pending suggestions 🎉".to_string(),
+            InlineKeyboardMarkup::default(),
+        ));
+    }
+    let now = Utc::now().timestamp();
+    let first_number = page * PENDING_PAGE_SIZE + 1;
+    let _list = posts
+        .iter()
+        .enumerate()
+        .map(|(index, post)| {
+            format!(
+                "{}. {} | {} | {}",
+                first_number + index as i64,
+                if post.is_anonymous {
+                    "🕶 anonymous"
+                } else {
+                    post.author.as_deref().unwrap_or("unknown")
+                },
+                post.kind.as_deref().unwrap_or("unknown"),
+                format_age(now, post.created_at)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    let admin_chat = admin_chat_id.to_string();
+    let links = posts
+        .iter()
+        .enumerate()
+        .filter_map(|(index, post)| {
+            channel_post_link(&admin_chat, post.admin_chat_message_id).map(|link| {
+                InlineKeyboardButton::url(format!("#{}", first_number + index as i64), link)
+            })
+        })
+        .collect::<Vec<InlineKeyboardButton>>();
+    let mut keyboard = InlineKeyboardMarkup::default();
+    for row in links.chunks(5) {
+        keyboard = keyboard.append_row(row.to_vec());
+    }
+    let mut navigation = Vec::new();
+    if page > 0 {
+        navigation.push(InlineKeyboardButton::callback(
+            "⬅️".to_string(),
+            format!("{}:{}", PENDING_PAGE_CALLBACK, page - 1),
+        ));
+    }
+    if page + 1 < pages {
+        navigation.push(InlineKeyboardButton::callback(
+            "➡️".to_string(),
+            format!("{}:{}", PENDING_PAGE_CALLBACK, page + 1),
+        ));
+    }
+    if !navigation.is_empty() {
+        keyboard = keyboard.append_row(navigation);
+    }
+    Ok((
+        format!(
+            "Pending suggestions: {} (page {}/{})\n{}",
+            count,
+            page + 1,
+            pages,
+            _list
+        ),
+        keyboard,
+    ))
+}
@@ -0,0 +1,434 @@
+use std::convert::Infallible;
+use std::env;
+use std::net::SocketAddr;
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server, StatusCode};
+use log::warn;
+use percent_encoding::percent_decode_str;
+use serde_json::{json, Value};
+use teloxide::prelude::*;
+use teloxide::types::User;
+use url::form_urlencoded;
+
+use crate::admin_commands::{remove_pic, save_pic, set_ban};
+use crate::data::model::offered_post::OfferedPostStatus;
+use crate::data::model::pic::Pic;
+use crate::data::repo::Repos;
+use crate::utils::env_utils::get_env_key;
+use crate::utils::error_utils::HandlerError;
+use crate::utils::request_utils::SendRetry;
+use crate::utils::result_utils::FatalValueMapper;
+use crate::{
+    apply_decision, restore_keyboard, ACCEPT_CALLBACK, ADMINS_CHAT_ID, DECLINE_CALLBACK,
+    PENDING_DECISIONS, SILENT_DECLINE_CALLBACK, VOTING_QUORUM, WITHOUT_TEXT_CALLBACK,
+};
+
+static ADMIN_API_ADDR_KEY: &str = "ADMIN_API_ADDR";
+static ADMIN_API_TOKEN_KEY: &str = "ADMIN_API_TOKEN";
+
+static MODERATOR_HEADER: &str = "X-Moderator";
+
+const PAGE_SIZE: i64 = 50;
+const ADMIN_API_TOKEN_MIN_LEN: usize = 16;
+
+#[derive(Clone)]
+pub struct ApiState {
+    pub bot: Bot,
+    pub repos: Repos,
+}
+
+struct ApiError(StatusCode, String);
+
+impl From<HandlerError> for ApiError {
+    fn from(e: HandlerError) -> Self {
+        let status = match &e {
+            HandlerError::Db(sqlx::Error::RowNotFound) | HandlerError::Missing(_) => {
+                StatusCode::NOT_FOUND
+            }
+            HandlerError::Permission(_) => StatusCode::FORBIDDEN,
+            HandlerError::Validation(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError(status, e.to_string())
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::RowNotFound => ApiError(StatusCode::NOT_FOUND, "Not found".to_string()),
+            e => ApiError(StatusCode::INTERNAL_SERVER_ERROR, format!("{:?}", e)),
+        }
+    }
+}
+
+pub fn start_admin_api(state: ApiState) {
+    let addr = match env::var(ADMIN_API_ADDR_KEY) {
+        Ok(addr) if !addr.is_empty() => addr
+            .parse::<SocketAddr>()
+            .map_value_or_exit(format!("Invalid {} value {}", ADMIN_API_ADDR_KEY, addr)),
+        _ => return,
+    };
+    let token = Some(get_env_key(ADMIN_API_TOKEN_KEY))
+        .filter(|token| token.len() >= ADMIN_API_TOKEN_MIN_LEN)
+        .ok_or(())
+        .map_value_or_exit(format!(
+            "{} should be at least {} characters long",
+            ADMIN_API_TOKEN_KEY, ADMIN_API_TOKEN_MIN_LEN
+        ));
+    let make_service = make_service_fn(move |_| {
+        let state = state.clone();
+        let token = token.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let state = state.clone();
+                let token = token.clone();
+                async move { Ok::<_, Infallible>(handle(request, &state, &token).await) }
+            }))
+        }
+    });
+    tokio::spawn(async move {
+        log::info!("Admin API is listening on {}", addr);
+        if let Err(e) = Server::bind(&addr).serve(make_service).await {
+            warn!("Admin API stopped, due to error {:?}", e);
+        }
+    });
+}
+
+async fn handle(request: Request<Body>, state: &ApiState, token: &str) -> Response<Body> {
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|value| constant_time_eq(value.as_bytes(), token.as_bytes()))
+        .unwrap_or(false);
+    if !authorized {
+        return error_response(ApiError(
+            StatusCode::UNAUTHORIZED,
+            "Unauthorized".to_string(),
+        ));
+    }
+    match route(request, state).await {
+        Ok(response) => response,
+        Err(e) => error_response(e),
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter()
+        .zip(b.iter())
+        .fold(0, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+async fn route(request: Request<Body>, state: &ApiState) -> Result<Response<Body>, ApiError> {
+    let path = request
+        .uri()
+        .path()
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| percent_decode_str(segment).decode_utf8_lossy().to_string())
+        .collect::<Vec<String>>();
+    let path = path
+        .iter()
+        .map(|segment| segment.as_str())
+        .collect::<Vec<&str>>();
+    let method = request.method().clone();
+    match (&method, path.as_slice()) {
+        (&Method::GET, ["pending"]) => pending(&request, state).await,
+        (&Method::POST, ["pending", admin_message_id, action]) => {
+            let data = match *action {
+                "accept" => ACCEPT_CALLBACK,
+                "accept-without-text" => WITHOUT_TEXT_CALLBACK,
+                "decline" => DECLINE_CALLBACK,
+                "decline-silent" => SILENT_DECLINE_CALLBACK,
+                _ => return Err(not_found()),
+            };
+            decide(&request, state, parse(admin_message_id)?, data).await
+        }
+        (&Method::POST, ["users", chat_id, "ban"]) => ban(state, parse(chat_id)?, true).await,
+        (&Method::POST, ["users", chat_id, "unban"]) => ban(state, parse(chat_id)?, false).await,
+        (&Method::GET, ["users", chat_id, "stats"]) => {
+            let stats = state
+                .repos
+                .stats_repo
+                .get_stat_for_user_or_default(parse(chat_id)?)
+                .await;
+            Ok(json_response(
+                StatusCode::OK,
+                json!({
+                    "chat_id": stats.user_id,
+                    "offered": stats.offered_count,
+                    "accepted": stats.accepted_count,
+                    "declined": stats.declined_count,
+                }),
+            ))
+        }
+        (&Method::GET, ["pics"]) => {
+            let pics = state.repos.pic_repo.get_all_pics().await?;
+            Ok(json_response(
+                StatusCode::OK,
+                Value::Array(
+                    pics.iter()
+                        .map(|pic| {
+                            json!({
+                                "file_name": pic.file_name,
+                                "for_accept": pic.for_accept,
+                            })
+                        })
+                        .collect(),
+                ),
+            ))
+        }
+        (&Method::GET, ["pics", mark, file_name]) => {
+            let pic = state
+                .repos
+                .pic_repo
+                .get_pic(file_name.to_string(), parse_mark(mark)?)
+                .await?;
+            Ok(Response::builder()
+                .header(header::CONTENT_TYPE, "image/gif")
+                .body(Body::from(pic.data))
+                .unwrap())
+        }
+        (&Method::PUT, ["pics", mark, file_name]) => {
+            let for_accept = parse_mark(mark)?;
+            if state
+                .repos
+                .pic_repo
+                .get_pic(file_name.to_string(), for_accept)
+                .await
+                .is_ok()
+            {
+                return Err(ApiError(
+                    StatusCode::CONFLICT,
+                    "Pic with this name and mark already exists".to_string(),
+                ));
+            }
+            let file_name = file_name.to_string();
+            let data = hyper::body::to_bytes(request.into_body())
+                .await
+                .map_err(|e| ApiError(StatusCode::BAD_REQUEST, format!("{:?}", e)))?;
+            save_pic(
+                &state.repos.pic_repo,
+                &state.repos.webhook_event_repo,
+                Pic::new(file_name, for_accept, data.to_vec()),
+            )
+            .await?;
+            Ok(json_response(StatusCode::CREATED, json!({ "ok": true })))
+        }
+        (&Method::DELETE, ["pics", mark, file_name]) => {
+            remove_pic(
+                &state.repos.pic_repo,
+                &state.repos.webhook_event_repo,
+                file_name.to_string(),
+                parse_mark(mark)?,
+            )
+            .await?;
+            Ok(json_response(StatusCode::OK, json!({ "ok": true })))
+        }
+        _ => Err(not_found()),
+    }
+}
+
+async fn pending(request: &Request<Body>, state: &ApiState) -> Result<Response<Body>, ApiError> {
+    let page = request
+        .uri()
+        .query()
+        .and_then(|query| {
+            form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == "page")
+                .and_then(|(_, value)| value.parse::<i64>().ok())
+        })
+        .unwrap_or(0)
+        .max(0);
+    let admin_chat_id = admin_chat_id()?;
+    let total = state
+        .repos
+        .offered_post_repo
+        .count_pending(admin_chat_id)
+        .await?;
+    let posts = state
+        .repos
+        .offered_post_repo
+        .get_pending_page(admin_chat_id, PAGE_SIZE, page * PAGE_SIZE)
+        .await?;
+    Ok(json_response(
+        StatusCode::OK,
+        json!({
+            "total": total,
+            "page": page,
+            "posts": posts
+                .iter()
+                .map(|post| {
+                    let mut item = json!({
+                        "admin_message_id": post.admin_chat_message_id,
+                        "author": post.author,
+                        "anonymous": post.is_anonymous,
+                        "kind": post.kind,
+                        "created_at": post.created_at,
+                    });
+                    if !post.is_anonymous {
+                        item["chat_id"] = json!(post.chat_id);
+                        item["message_id"] = json!(post.message_id);
+                    }
+                    item
+                })
+                .collect::<Vec<Value>>(),
+        }),
+    ))
+}
+
+async fn decide(
+    request: &Request<Body>,
+    state: &ApiState,
+    admin_message_id: i32,
+    data: &str,
+) -> Result<Response<Body>, ApiError> {
+    let admin_chat_id = admin_chat_id()?;
+    let post = state
+        .repos
+        .offered_post_repo
+        .get_offered_post(admin_chat_id, admin_message_id)
+        .await?;
+    let decidable = matches!(
+        post.status,
+        OfferedPostStatus::Pending | OfferedPostStatus::Unknown
+    );
+    if !decidable
+        || PENDING_DECISIONS
+            .lock()
+            .unwrap()
+            .contains_key(&(admin_chat_id, admin_message_id))
+    {
+        return Err(ApiError(
+            StatusCode::CONFLICT,
+            "Suggestion is already decided".to_string(),
+        ));
+    }
+    let tally = if *VOTING_QUORUM > 0 {
+        Some(
+            state
+                .repos
+                .vote_repo
+                .get_tally(admin_chat_id, admin_message_id)
+                .await?,
+        )
+    } else {
+        None
+    };
+    let message = state
+        .bot
+        .edit_message_reply_markup(admin_chat_id, admin_message_id)
+        .send_retry()
+        .await
+        .map_err(HandlerError::from)?;
+    let origin = match message.reply_to_message() {
+        Some(origin) => origin,
+        None => {
+            restore_keyboard(&state.bot, &message, None, tally).await;
+            return Err(HandlerError::missing("Reply message are missing").into());
+        }
+    };
+    let moderator = User {
+        id: 0,
+        is_bot: false,
+        first_name: request
+            .headers()
+            .get(MODERATOR_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("Admin API")
+            .to_string(),
+        last_name: None,
+        username: None,
+        language_code: None,
+    };
+    if let Err(e) = apply_decision(
+        &state.bot,
+        &data.to_string(),
+        &message,
+        origin,
+        &moderator,
+        &state.repos,
+    )
+    .await
+    {
+        restore_keyboard(&state.bot, &message, Some(origin), tally).await;
+        return Err(e.into());
+    }
+    let post = state
+        .repos
+        .offered_post_repo
+        .get_offered_post(admin_chat_id, admin_message_id)
+        .await?;
+    Ok(json_response(
+        StatusCode::OK,
+        json!({
+            "status": post.status.as_str(),
+            "channel_message_id": post.channel_message_id,
+        }),
+    ))
+}
+
+async fn ban(state: &ApiState, chat_id: i64, is_ban: bool) -> Result<Response<Body>, ApiError> {
+    set_ban(
+        &state.repos.ban_repo,
+        &state.repos.webhook_event_repo,
+        chat_id,
+        chat_id.to_string(),
+        chrono::Utc::now().timestamp().to_string(),
+        is_ban,
+    )
+    .await?;
+    Ok(json_response(StatusCode::OK, json!({ "ok": true })))
+}
+
+fn admin_chat_id() -> Result<i64, ApiError> {
+    ADMINS_CHAT_ID.parse::<i64>().map_err(|_| {
+        ApiError(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Admin chat id is not numeric".to_string(),
+        )
+    })
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> Result<T, ApiError> {
+    value.parse::<T>().map_err(|_| {
+        ApiError(
+            StatusCode::BAD_REQUEST,
+            format!("Invalid parameter {}", value),
+        )
+    })
+}
+
+fn parse_mark(mark: &str) -> Result<bool, ApiError> {
+    match mark {
+        "A" => Ok(true),
+        "D" => Ok(false),
+        _ => Err(ApiError(
+            StatusCode::BAD_REQUEST,
+            "Mark should be A or D".to_string(),
+        )),
+    }
+}
+
+fn not_found() -> ApiError {
+    ApiError(StatusCode::NOT_FOUND, "Not found".to_string())
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn error_response(e: ApiError) -> Response<Body> {
+    json_response(e.0, json!({ "error": e.1 }))
+}
@@ -0,0 +1,257 @@
+use std::env;
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+use log::warn;
+use reqwest::Client;
+use serde::Serialize;
+use teloxide::prelude::*;
+use tokio::time::sleep;
+
+use crate::utils::error_utils::HandlerError;
+
+static CROSSPOST_CHATS_KEY: &str = "CROSSPOST_CHATS";
+static CROSSPOST_WEBHOOK_KEY: &str = "CROSSPOST_WEBHOOK";
+static CROSSPOST_RETRIES_KEY: &str = "CROSSPOST_RETRIES";
+
+lazy_static! {
+    static ref CROSSPOST_CHATS: Vec<String> = env::var(CROSSPOST_CHATS_KEY)
+        .unwrap_or(String::new())
+        .split(',')
+        .map(|chat| chat.trim().to_string())
+        .filter(|chat| !chat.is_empty())
+        .collect();
+    static ref CROSSPOST_WEBHOOK: Option<String> = env::var(CROSSPOST_WEBHOOK_KEY)
+        .ok()
+        .filter(|url| !url.is_empty());
+    static ref CROSSPOST_RETRIES: u32 = env::var(CROSSPOST_RETRIES_KEY)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(3);
+    static ref CLIENT: Client = Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap_or_default();
+}
+
+#[derive(Clone, Serialize)]
+pub struct CrosspostPayload {
+    pub channel_id: String,
+    pub message_id: i32,
+    pub link: Option<String>,
+    pub author: Option<String>,
+    pub kind: Option<String>,
+    pub text: Option<String>,
+}
+
+pub fn is_crosspost_enabled() -> bool {
+    !CROSSPOST_CHATS.is_empty() || CROSSPOST_WEBHOOK.is_some()
+}
+
+pub fn start_crosspost(bot: Bot, payload: CrosspostPayload, admins_chat_id: String, reply_to: i32) {
+    tokio::spawn(async move {
+        for chat in CROSSPOST_CHATS.iter() {
+            let result = with_retries(|| {
+                copy_to_chat(
+                    &bot,
+                    chat.to_string(),
+                    &payload.channel_id,
+                    payload.message_id,
+                )
+            })
+            .await;
+            if let Err(e) = result {
+                report_failure(&bot, &admins_chat_id, reply_to, chat, e).await;
+            }
+        }
+        if let Some(url) = CROSSPOST_WEBHOOK.as_ref() {
+            if let Err(e) = with_retries(|| send_webhook(url, &payload)).await {
+                report_failure(&bot, &admins_chat_id, reply_to, url, e).await;
+            }
+        }
+    });
+}
+
+async fn with_retries<F, Fut>(action: F) -> Result<(), HandlerError>
+where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = Result<(), HandlerError>>,
+{
+    let mut attempt = 0;
+    loop {
+        match action().await {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt + 1 >= *CROSSPOST_RETRIES => return Err(e),
+            Err(e) => {
+                warn!(
+                    "Cross-post attempt {} failed, due to error {:?}",
+                    attempt + 1,
+                    e
+                );
+                sleep(Duration::from_secs(2u64.pow(attempt))).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+async fn copy_to_chat(
+    bot: &Bot,
+    chat: String,
+    channel_id: &str,
+    message_id: i32,
+) -> Result<(), HandlerError> {
+    bot.copy_message(chat, channel_id.to_string(), message_id)
+        .send()
+        .await?;
+    Ok(())
+}
+
+async fn send_webhook(url: &str, payload: &CrosspostPayload) -> Result<(), HandlerError> {
+    CLIENT
+        .post(url)
+        .json(payload)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| HandlerError::External(format!("Webhook error: {:?}", e)))?;
+    Ok(())
+}
+
+async fn report_failure(
+    bot: &Bot,
+    admins_chat_id: &str,
+    reply_to: i32,
+    destination: &str,
+    e: HandlerError,
+) {
+    warn!(
+        "Can not cross-post to {}, due to error {:?}",
+        destination, e
+    );
+    let _ = bot
+        .send_message(
+            admins_chat_id.to_string(),
+            format!("⚠️ Cross-post to {} failed.\n{}", destination, e),
+        )
+        .reply_to_message_id(reply_to)
+        .send()
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Response, Server, StatusCode};
+
+    use super::*;
+
+    struct StandIn {
+        url: String,
+        hits: Arc<AtomicUsize>,
+        bodies: Arc<Mutex<Vec<serde_json::Value>>>,
+    }
+
+    fn start_stand_in(failures: usize) -> StandIn {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let service_hits = hits.clone();
+        let service_bodies = bodies.clone();
+        let make_service = make_service_fn(move |_| {
+            let hits = service_hits.clone();
+            let bodies = service_bodies.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let hits = hits.clone();
+                    let bodies = bodies.clone();
+                    async move {
+                        let data = hyper::body::to_bytes(request.into_body())
+                            .await
+                            .unwrap_or_default();
+                        if let Ok(body) = serde_json::from_slice(&data) {
+                            bodies.lock().unwrap().push(body);
+                        }
+                        let status = if hits.fetch_add(1, Ordering::SeqCst) < failures {
+                            StatusCode::INTERNAL_SERVER_ERROR
+                        } else {
+                            StatusCode::OK
+                        };
+                        let mut response = Response::new(Body::empty());
+                        *response.status_mut() = status;
+                        Ok::<_, Infallible>(response)
+                    }
+                }))
+            }
+        });
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+        let url = format!("http://{}/hook", server.local_addr());
+        tokio::spawn(server);
+        StandIn { url, hits, bodies }
+    }
+
+    fn payload() -> CrosspostPayload {
+        CrosspostPayload {
+            channel_id: "@channel".to_string(),
+            message_id: 42,
+            link: Some("https://t.me/channel/42".to_string()),
+            author: Some("author".to_string()),
+            kind: Some("photo".to_string()),
+            text: Some("caption".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn delivers_payload_to_webhook() {
+        let stand_in = start_stand_in(0);
+        send_webhook(&stand_in.url, &payload()).await.unwrap();
+        assert_eq!(stand_in.hits.load(Ordering::SeqCst), 1);
+        let bodies = stand_in.bodies.lock().unwrap();
+        assert_eq!(
+            bodies[0],
+            serde_json::json!({
+                "channel_id": "@channel",
+                "message_id": 42,
+                "link": "https://t.me/channel/42",
+                "author": "author",
+                "kind": "photo",
+                "text": "caption",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_failing_status_as_error() {
+        let stand_in = start_stand_in(1);
+        assert!(send_webhook(&stand_in.url, &payload()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn retries_failing_webhook_until_it_succeeds() {
+        let attempts = *CROSSPOST_RETRIES as usize;
+        let stand_in = start_stand_in(attempts - 1);
+        let payload = payload();
+        with_retries(|| send_webhook(&stand_in.url, &payload))
+            .await
+            .unwrap();
+        assert_eq!(stand_in.hits.load(Ordering::SeqCst), attempts);
+        assert_eq!(stand_in.bodies.lock().unwrap().len(), attempts);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_configured_retries() {
+        let stand_in = start_stand_in(usize::MAX);
+        let payload = payload();
+        assert!(with_retries(|| send_webhook(&stand_in.url, &payload))
+            .await
+            .is_err());
+        assert_eq!(
+            stand_in.hits.load(Ordering::SeqCst),
+            *CROSSPOST_RETRIES as usize
+        );
+    }
+}
@@ -1,5 +1,9 @@
 pub mod cached_pic;
 pub mod offered_post;
 pub mod pic;
+pub mod relay_message;
+pub mod scheduled_decision;
 pub mod stats;
 pub mod ban;
+pub mod user_settings;
+pub mod webhook_event;
@@ -1,9 +1,52 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OfferedPostStatus {
+    Pending,
+    Deciding,
+    Accepted,
+    Declined,
+    Withdrawn,
+    Unpublished,
+    Unknown,
+}
+
+impl OfferedPostStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OfferedPostStatus::Pending => "pending",
+            OfferedPostStatus::Deciding => "deciding",
+            OfferedPostStatus::Accepted => "accepted",
+            OfferedPostStatus::Declined => "declined",
+            OfferedPostStatus::Withdrawn => "withdrawn",
+            OfferedPostStatus::Unpublished => "unpublished",
+            OfferedPostStatus::Unknown => "unknown",
+        }
+    }
+
+    pub fn from_str(value: &str) -> Self {
+        match value {
+            "pending" => OfferedPostStatus::Pending,
+            "deciding" => OfferedPostStatus::Deciding,
+            "accepted" => OfferedPostStatus::Accepted,
+            "declined" => OfferedPostStatus::Declined,
+            "withdrawn" => OfferedPostStatus::Withdrawn,
+            "unpublished" => OfferedPostStatus::Unpublished,
+            _ => OfferedPostStatus::Unknown,
+        }
+    }
+}
+
 pub struct OfferedPost {
     pub chat_id: i64,
     pub message_id: i32,
     pub admin_chat_id: i64,
     pub admin_chat_message_id: i32,
     pub admin_chat_additional_message_id: Option<i32>,
+    pub status: OfferedPostStatus,
+    pub channel_message_id: Option<i32>,
+    pub author: Option<String>,
+    pub is_anonymous: bool,
+    pub created_at: i64,
+    pub kind: Option<String>,
 }
 
 impl OfferedPost {
@@ -13,6 +56,7 @@ impl OfferedPost {
         admin_chat_id: i64,
         admin_chat_message_id: i32,
         admin_chat_additional_message_id: Option<i32>,
+        created_at: i64,
     ) -> Self {
         OfferedPost {
             chat_id,
@@ -20,6 +64,12 @@ impl OfferedPost {
             admin_chat_id,
             admin_chat_message_id,
             admin_chat_additional_message_id,
+            status: OfferedPostStatus::Pending,
+            channel_message_id: None,
+            author: None,
+            is_anonymous: false,
+            created_at,
+            kind: None,
         }
     }
 }
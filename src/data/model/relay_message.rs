@@ -0,0 +1,22 @@
+pub struct RelayMessage {
+    pub admin_chat_id: i64,
+    pub admin_message_id: i32,
+    pub user_chat_id: i64,
+    pub user_message_id: i32,
+}
+
+impl RelayMessage {
+    pub fn new(
+        admin_chat_id: i64,
+        admin_message_id: i32,
+        user_chat_id: i64,
+        user_message_id: i32,
+    ) -> Self {
+        RelayMessage {
+            admin_chat_id,
+            admin_message_id,
+            user_chat_id,
+            user_message_id,
+        }
+    }
+}
@@ -0,0 +1,7 @@
+pub struct ScheduledDecision {
+    pub admin_chat_id: i64,
+    pub admin_chat_message_id: i32,
+    pub data: String,
+    pub moderator: String,
+    pub due_at: i64,
+}
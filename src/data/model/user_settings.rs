@@ -0,0 +1,17 @@
+pub struct UserSettings {
+    pub chat_id: i64,
+    pub credit: bool,
+    pub anonymous: bool,
+    pub anon_token: Option<String>,
+}
+
+impl UserSettings {
+    pub fn empty(chat_id: i64) -> Self {
+        UserSettings {
+            chat_id,
+            credit: false,
+            anonymous: false,
+            anon_token: None,
+        }
+    }
+}
@@ -0,0 +1,8 @@
+pub struct WebhookEvent {
+    pub id: i64,
+    pub url: String,
+    pub event: String,
+    pub payload: String,
+    pub attempts: i32,
+    pub next_attempt_at: i64,
+}
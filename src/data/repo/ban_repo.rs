@@ -1,6 +1,7 @@
-use sqlx::{Error, Pool, Sqlite};
+use sqlx::{Error, Pool, Sqlite, Transaction};
 
 use crate::data::model::ban::Ban;
+use crate::metrics::query_timer;
 
 #[derive(Clone)]
 pub struct BanRepo {
@@ -13,6 +14,19 @@ impl BanRepo {
     }
 
     pub async fn create(&self, chat_id: i64, user_name: String, date: String) -> Result<(), Error> {
+        let mut tx = self.pool.begin().await?;
+        self.create_in(&mut tx, chat_id, user_name, date).await?;
+        tx.commit().await
+    }
+
+    pub async fn create_in(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        chat_id: i64,
+        user_name: String,
+        date: String,
+    ) -> Result<(), Error> {
+        let _timer = query_timer("ban.create");
         sqlx::query!(
             "INSERT OR IGNORE INTO bans(chat_id, user_name, date, is_ban) VALUES (?, ?, ?, ?)",
             chat_id,
@@ -20,12 +34,13 @@ impl BanRepo {
             date,
             false,
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
         Ok(())
     }
 
     pub async fn get_list(&self) -> Result<Vec<Ban>, Error> {
+        let _timer = query_timer("ban.get_list");
         Ok(
             sqlx::query_as!(Ban, "SELECT * FROM bans WHERE is_ban = 1")
                 .fetch_all(&self.pool)
@@ -34,6 +49,7 @@ impl BanRepo {
     }
 
     pub async fn is_banned(&self, chat_id: i64) -> Result<bool, Error> {
+        let _timer = query_timer("ban.is_banned");
         let result = sqlx::query!("SELECT * FROM bans WHERE chat_id = ?", chat_id)
             .fetch_one(&self.pool)
             .await?;
@@ -41,6 +57,7 @@ impl BanRepo {
     }
 
     pub async fn update(&self, chat_id: i64, date: String, is_ban: bool) -> Result<(), Error> {
+        let _timer = query_timer("ban.update");
         sqlx::query!(
             "UPDATE bans SET is_ban = ?, date = ? WHERE chat_id = ?",
             is_ban,
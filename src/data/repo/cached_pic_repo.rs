@@ -1,6 +1,7 @@
 use sqlx::{Error, Pool, Sqlite};
 
 use crate::data::model::cached_pic::CachedPic;
+use crate::metrics::query_timer;
 
 #[derive(Clone)]
 pub struct CachedPicRepo {
@@ -13,6 +14,7 @@ impl CachedPicRepo {
     }
 
     pub async fn save_cached_pic(&self, cached_pic: CachedPic) -> Result<(), Error> {
+        let _timer = query_timer("cached_pic.save_cached_pic");
         sqlx::query!(
             "INSERT INTO cached_pic (image_name, image_file_id) VALUES (?, ?)",
             cached_pic.image_name,
@@ -24,6 +26,7 @@ impl CachedPicRepo {
     }
 
     pub async fn get_cached_pic(&self, image_name: String) -> Result<CachedPic, Error> {
+        let _timer = query_timer("cached_pic.get_cached_pic");
         let result = sqlx::query!("SELECT * FROM cached_pic WHERE image_name = ?", image_name)
             .fetch_one(&self.pool)
             .await?;
@@ -1,5 +1,50 @@
+use sqlx::{Pool, Sqlite};
+
+use crate::data::repo::ban_repo::BanRepo;
+use crate::data::repo::cached_pic_repo::CachedPicRepo;
+use crate::data::repo::offered_post_repo::OfferedPostRepo;
+use crate::data::repo::pic_repo::PicRepo;
+use crate::data::repo::relay_message_repo::RelayMessageRepo;
+use crate::data::repo::stats_repo::StatsRepo;
+use crate::data::repo::user_settings_repo::UserSettingsRepo;
+use crate::data::repo::vote_repo::VoteRepo;
+use crate::data::repo::webhook_event_repo::WebhookEventRepo;
+
+pub mod ban_repo;
 pub mod cached_pic_repo;
 pub mod offered_post_repo;
 pub mod pic_repo;
+pub mod relay_message_repo;
 pub mod stats_repo;
-pub mod ban_repo;
+pub mod user_settings_repo;
+pub mod vote_repo;
+pub mod webhook_event_repo;
+
+#[derive(Clone)]
+pub struct Repos {
+    pub offered_post_repo: OfferedPostRepo,
+    pub cached_pic_repo: CachedPicRepo,
+    pub pic_repo: PicRepo,
+    pub stats_repo: StatsRepo,
+    pub ban_repo: BanRepo,
+    pub user_settings_repo: UserSettingsRepo,
+    pub relay_message_repo: RelayMessageRepo,
+    pub vote_repo: VoteRepo,
+    pub webhook_event_repo: WebhookEventRepo,
+}
+
+impl Repos {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Repos {
+            offered_post_repo: OfferedPostRepo::new(pool.clone()),
+            cached_pic_repo: CachedPicRepo::new(pool.clone()),
+            pic_repo: PicRepo::new(pool.clone()),
+            stats_repo: StatsRepo::new(pool.clone()),
+            ban_repo: BanRepo::new(pool.clone()),
+            user_settings_repo: UserSettingsRepo::new(pool.clone()),
+            relay_message_repo: RelayMessageRepo::new(pool.clone()),
+            vote_repo: VoteRepo::new(pool.clone()),
+            webhook_event_repo: WebhookEventRepo::new(pool),
+        }
+    }
+}
@@ -1,29 +1,82 @@
 use std::convert::TryInto;
 
-use sqlx::{Error, Pool, Sqlite};
+use sqlx::{Error, Pool, Sqlite, Transaction};
 
-use crate::data::model::offered_post::OfferedPost;
+use crate::data::model::offered_post::{OfferedPost, OfferedPostStatus};
+use crate::data::model::scheduled_decision::ScheduledDecision;
+use crate::metrics::query_timer;
 
 #[derive(Clone)]
 pub struct OfferedPostRepo {
     pool: Pool<Sqlite>,
 }
 
+struct OfferedPostRow {
+    chat_id: i64,
+    message_id: i64,
+    admin_chat_id: i64,
+    admin_chat_message_id: i64,
+    admin_chat_additional_message_id: Option<i64>,
+    status: String,
+    channel_message_id: Option<i64>,
+    author: Option<String>,
+    is_anonymous: bool,
+    created_at: i64,
+    kind: Option<String>,
+}
+
+impl From<OfferedPostRow> for OfferedPost {
+    fn from(row: OfferedPostRow) -> Self {
+        OfferedPost {
+            chat_id: row.chat_id,
+            message_id: row.message_id.try_into().unwrap(),
+            admin_chat_id: row.admin_chat_id,
+            admin_chat_message_id: row.admin_chat_message_id.try_into().unwrap(),
+            admin_chat_additional_message_id: row
+                .admin_chat_additional_message_id
+                .and_then(|val| Some(val.try_into().unwrap())),
+            status: OfferedPostStatus::from_str(&row.status),
+            channel_message_id: row
+                .channel_message_id
+                .and_then(|val| Some(val.try_into().unwrap())),
+            author: row.author,
+            is_anonymous: row.is_anonymous,
+            created_at: row.created_at,
+            kind: row.kind,
+        }
+    }
+}
+
 impl OfferedPostRepo {
     pub fn new(pool: Pool<Sqlite>) -> Self {
         OfferedPostRepo { pool }
     }
 
-    pub async fn save_offered_post(&self, offered_post: OfferedPost) -> Result<(), Error> {
+    pub async fn begin(&self) -> Result<Transaction<'static, Sqlite>, Error> {
+        self.pool.begin().await
+    }
+
+    pub async fn save_offered_post(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        offered_post: OfferedPost,
+    ) -> Result<(), Error> {
+        let _timer = query_timer("offered_post.save_offered_post");
+        let status = offered_post.status.as_str();
         sqlx::query!(
-            "INSERT INTO offered_post (message_id, chat_id, admin_chat_id, admin_chat_message_id, admin_chat_additional_message_id) VALUES (?, ?, ?, ?, ?)",
+            "INSERT INTO offered_post (message_id, chat_id, admin_chat_id, admin_chat_message_id, admin_chat_additional_message_id, status, author, is_anonymous, created_at, kind) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             offered_post.message_id,
             offered_post.chat_id,
             offered_post.admin_chat_id,
             offered_post.admin_chat_message_id,
             offered_post.admin_chat_additional_message_id,
+            status,
+            offered_post.author,
+            offered_post.is_anonymous,
+            offered_post.created_at,
+            offered_post.kind,
         )
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
         Ok(())
     }
@@ -33,21 +86,367 @@ impl OfferedPostRepo {
         admin_chat_id: i64,
         admin_chat_message_id: i32,
     ) -> Result<OfferedPost, Error> {
+        let _timer = query_timer("offered_post.get_offered_post");
+        let result = sqlx::query_as!(
+            OfferedPostRow,
+            "SELECT chat_id, message_id, admin_chat_id, admin_chat_message_id, admin_chat_additional_message_id, status, channel_message_id, author, is_anonymous, created_at, kind FROM offered_post WHERE admin_chat_id = $1 AND (admin_chat_message_id = $2 OR admin_chat_additional_message_id = $2)",
+            admin_chat_id,
+            admin_chat_message_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(result.into())
+    }
+
+    pub async fn get_user_offered_post(
+        &self,
+        chat_id: i64,
+        message_id: i32,
+    ) -> Result<OfferedPost, Error> {
+        let _timer = query_timer("offered_post.get_user_offered_post");
+        let result = sqlx::query_as!(
+            OfferedPostRow,
+            "SELECT chat_id, message_id, admin_chat_id, admin_chat_message_id, admin_chat_additional_message_id, status, channel_message_id, author, is_anonymous, created_at, kind FROM offered_post WHERE chat_id = ? AND message_id = ? ORDER BY id DESC LIMIT 1",
+            chat_id,
+            message_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(result.into())
+    }
+
+    pub async fn get_pending_offered_posts(&self, chat_id: i64) -> Result<Vec<OfferedPost>, Error> {
+        let _timer = query_timer("offered_post.get_pending_offered_posts");
+        let pending = OfferedPostStatus::Pending.as_str();
+        let result = sqlx::query_as!(
+            OfferedPostRow,
+            "SELECT chat_id, message_id, admin_chat_id, admin_chat_message_id, admin_chat_additional_message_id, status, channel_message_id, author, is_anonymous, created_at, kind FROM offered_post WHERE chat_id = ? AND status = ? ORDER BY id",
+            chat_id,
+            pending
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result.into_iter().map(|row| row.into()).collect())
+    }
+
+    pub async fn update_status(
+        &self,
+        admin_chat_id: i64,
+        admin_chat_message_id: i32,
+        status: OfferedPostStatus,
+    ) -> Result<(), Error> {
+        let _timer = query_timer("offered_post.update_status");
+        let status = status.as_str();
+        sqlx::query!(
+            "UPDATE offered_post SET status = $1 WHERE admin_chat_id = $2 AND (admin_chat_message_id = $3 OR admin_chat_additional_message_id = $3)",
+            status,
+            admin_chat_id,
+            admin_chat_message_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn claim_decision(
+        &self,
+        admin_chat_id: i64,
+        admin_chat_message_id: i32,
+    ) -> Result<bool, Error> {
+        let _timer = query_timer("offered_post.claim_decision");
+        let deciding = OfferedPostStatus::Deciding.as_str();
+        let pending = OfferedPostStatus::Pending.as_str();
+        let unknown = OfferedPostStatus::Unknown.as_str();
         let result = sqlx::query!(
-            "SELECT * FROM offered_post WHERE admin_chat_id = $1 AND (admin_chat_message_id = $2 OR admin_chat_additional_message_id = $2)",
+            "UPDATE offered_post SET status = $1 WHERE admin_chat_id = $2 AND admin_chat_message_id = $3 AND status IN ($4, $5)",
+            deciding,
+            admin_chat_id,
+            admin_chat_message_id,
+            pending,
+            unknown
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn release_decision(
+        &self,
+        admin_chat_id: i64,
+        admin_chat_message_id: i32,
+    ) -> Result<(), Error> {
+        let _timer = query_timer("offered_post.release_decision");
+        let pending = OfferedPostStatus::Pending.as_str();
+        let deciding = OfferedPostStatus::Deciding.as_str();
+        sqlx::query!(
+            "UPDATE offered_post SET status = $1 WHERE admin_chat_id = $2 AND admin_chat_message_id = $3 AND status = $4",
+            pending,
+            admin_chat_id,
+            admin_chat_message_id,
+            deciding
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn release_all_decisions(&self) -> Result<(), Error> {
+        let _timer = query_timer("offered_post.release_all_decisions");
+        let pending = OfferedPostStatus::Pending.as_str();
+        let deciding = OfferedPostStatus::Deciding.as_str();
+        sqlx::query!(
+            "UPDATE offered_post SET status = $1 WHERE status = $2",
+            pending,
+            deciding
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn save_scheduled_decision(
+        &self,
+        admin_chat_id: i64,
+        admin_chat_message_id: i32,
+        data: &str,
+        moderator: String,
+        due_at: i64,
+    ) -> Result<(), Error> {
+        let _timer = query_timer("offered_post.save_scheduled_decision");
+        sqlx::query!(
+            "UPDATE offered_post SET scheduled_decision = $1, scheduled_by = $2, scheduled_at = $3 WHERE admin_chat_id = $4 AND admin_chat_message_id = $5",
+            data,
+            moderator,
+            due_at,
             admin_chat_id,
             admin_chat_message_id
         )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn clear_scheduled_decision(
+        &self,
+        admin_chat_id: i64,
+        admin_chat_message_id: i32,
+    ) -> Result<(), Error> {
+        let _timer = query_timer("offered_post.clear_scheduled_decision");
+        sqlx::query!(
+            "UPDATE offered_post SET scheduled_decision = NULL WHERE admin_chat_id = $1 AND admin_chat_message_id = $2",
+            admin_chat_id,
+            admin_chat_message_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_scheduled_decisions(&self) -> Result<Vec<ScheduledDecision>, Error> {
+        let _timer = query_timer("offered_post.get_scheduled_decisions");
+        let pending = OfferedPostStatus::Pending.as_str();
+        let result = sqlx::query!(
+            r#"SELECT admin_chat_id, admin_chat_message_id, scheduled_decision AS "scheduled_decision!", scheduled_by AS "scheduled_by!", scheduled_at AS "scheduled_at!" FROM offered_post WHERE status = ? AND scheduled_decision IS NOT NULL"#,
+            pending
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result
+            .into_iter()
+            .map(|row| ScheduledDecision {
+                admin_chat_id: row.admin_chat_id,
+                admin_chat_message_id: row.admin_chat_message_id.try_into().unwrap(),
+                data: row.scheduled_decision,
+                moderator: row.scheduled_by,
+                due_at: row.scheduled_at,
+            })
+            .collect())
+    }
+
+    pub async fn update_admin_messages(
+        &self,
+        chat_id: i64,
+        message_id: i32,
+        admin_chat_message_id: i32,
+        admin_chat_additional_message_id: Option<i32>,
+    ) -> Result<(), Error> {
+        let _timer = query_timer("offered_post.update_admin_messages");
+        sqlx::query!(
+            "UPDATE offered_post SET admin_chat_message_id = ?, admin_chat_additional_message_id = ?, scheduled_decision = NULL WHERE chat_id = ? AND message_id = ?",
+            admin_chat_message_id,
+            admin_chat_additional_message_id,
+            chat_id,
+            message_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_channel_message_id(
+        &self,
+        admin_chat_id: i64,
+        admin_chat_message_id: i32,
+        channel_message_id: i32,
+    ) -> Result<(), Error> {
+        let _timer = query_timer("offered_post.update_channel_message_id");
+        sqlx::query!(
+            "UPDATE offered_post SET channel_message_id = $1 WHERE admin_chat_id = $2 AND (admin_chat_message_id = $3 OR admin_chat_additional_message_id = $3)",
+            channel_message_id,
+            admin_chat_id,
+            admin_chat_message_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_pending_page(
+        &self,
+        admin_chat_id: i64,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<OfferedPost>, Error> {
+        let _timer = query_timer("offered_post.get_pending_page");
+        let pending = OfferedPostStatus::Pending.as_str();
+        let result = sqlx::query_as!(
+            OfferedPostRow,
+            "SELECT chat_id, message_id, admin_chat_id, admin_chat_message_id, admin_chat_additional_message_id, status, channel_message_id, author, is_anonymous, created_at, kind FROM offered_post WHERE admin_chat_id = ? AND status = ? ORDER BY id LIMIT ? OFFSET ?",
+            admin_chat_id,
+            pending,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result.into_iter().map(|row| row.into()).collect())
+    }
+
+    pub async fn count_pending(&self, admin_chat_id: i64) -> Result<i64, Error> {
+        let _timer = query_timer("offered_post.count_pending");
+        let pending = OfferedPostStatus::Pending.as_str();
+        let result = sqlx::query!(
+            r#"SELECT COUNT(*) AS "count!: i64" FROM offered_post WHERE admin_chat_id = ? AND status = ?"#,
+            admin_chat_id,
+            pending
+        )
         .fetch_one(&self.pool)
         .await?;
-        Ok(OfferedPost::new(
-            result.chat_id,
-            result.message_id.try_into().unwrap(),
-            result.admin_chat_id,
-            result.admin_chat_message_id.try_into().unwrap(),
-            result
-                .admin_chat_additional_message_id
-                .and_then(|val| Some(val.try_into().unwrap())),
-        ))
+        Ok(result.count)
+    }
+
+    pub async fn get_pending_older_than(
+        &self,
+        created_before: i64,
+    ) -> Result<Vec<OfferedPost>, Error> {
+        let _timer = query_timer("offered_post.get_pending_older_than");
+        let pending = OfferedPostStatus::Pending.as_str();
+        let result = sqlx::query_as!(
+            OfferedPostRow,
+            "SELECT chat_id, message_id, admin_chat_id, admin_chat_message_id, admin_chat_additional_message_id, status, channel_message_id, author, is_anonymous, created_at, kind FROM offered_post WHERE status = ? AND created_at > 0 AND created_at < ? ORDER BY id",
+            pending,
+            created_before
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result.into_iter().map(|row| row.into()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+
+    const ADMIN_CHAT_ID: i64 = -100;
+
+    async fn repo() -> OfferedPostRepo {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+        OfferedPostRepo::new(pool)
+    }
+
+    async fn save(repo: &OfferedPostRepo, admin_chat_message_id: i32) {
+        let mut tx = repo.begin().await.unwrap();
+        repo.save_offered_post(
+            &mut tx,
+            OfferedPost::new(
+                1,
+                admin_chat_message_id,
+                ADMIN_CHAT_ID,
+                admin_chat_message_id,
+                None,
+                0,
+            ),
+        )
+        .await
+        .unwrap();
+        tx.commit().await.unwrap();
+    }
+
+    async fn status(repo: &OfferedPostRepo, admin_chat_message_id: i32) -> OfferedPostStatus {
+        repo.get_offered_post(ADMIN_CHAT_ID, admin_chat_message_id)
+            .await
+            .unwrap()
+            .status
+    }
+
+    #[tokio::test]
+    async fn claims_pending_post_once() {
+        let repo = repo().await;
+        save(&repo, 1).await;
+        assert!(repo.claim_decision(ADMIN_CHAT_ID, 1).await.unwrap());
+        assert_eq!(status(&repo, 1).await, OfferedPostStatus::Deciding);
+        assert!(!repo.claim_decision(ADMIN_CHAT_ID, 1).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn release_makes_post_claimable_again() {
+        let repo = repo().await;
+        save(&repo, 1).await;
+        assert!(repo.claim_decision(ADMIN_CHAT_ID, 1).await.unwrap());
+        repo.release_decision(ADMIN_CHAT_ID, 1).await.unwrap();
+        assert_eq!(status(&repo, 1).await, OfferedPostStatus::Pending);
+        assert!(repo.claim_decision(ADMIN_CHAT_ID, 1).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn release_keeps_decided_post() {
+        let repo = repo().await;
+        save(&repo, 1).await;
+        repo.update_status(ADMIN_CHAT_ID, 1, OfferedPostStatus::Accepted)
+            .await
+            .unwrap();
+        repo.release_decision(ADMIN_CHAT_ID, 1).await.unwrap();
+        assert_eq!(status(&repo, 1).await, OfferedPostStatus::Accepted);
+        assert!(!repo.claim_decision(ADMIN_CHAT_ID, 1).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn claims_post_saved_before_status_tracking() {
+        let repo = repo().await;
+        save(&repo, 1).await;
+        repo.update_status(ADMIN_CHAT_ID, 1, OfferedPostStatus::Unknown)
+            .await
+            .unwrap();
+        assert!(repo.claim_decision(ADMIN_CHAT_ID, 1).await.unwrap());
+        assert_eq!(status(&repo, 1).await, OfferedPostStatus::Deciding);
+    }
+
+    #[tokio::test]
+    async fn release_all_frees_interrupted_claims() {
+        let repo = repo().await;
+        save(&repo, 1).await;
+        save(&repo, 2).await;
+        assert!(repo.claim_decision(ADMIN_CHAT_ID, 1).await.unwrap());
+        assert!(repo.claim_decision(ADMIN_CHAT_ID, 2).await.unwrap());
+        repo.release_all_decisions().await.unwrap();
+        assert_eq!(status(&repo, 1).await, OfferedPostStatus::Pending);
+        assert_eq!(status(&repo, 2).await, OfferedPostStatus::Pending);
     }
 }
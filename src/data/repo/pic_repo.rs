@@ -1,6 +1,7 @@
 use sqlx::{Error, Pool, Sqlite};
 
 use crate::data::model::pic::Pic;
+use crate::metrics::query_timer;
 
 #[derive(Clone)]
 pub struct PicRepo {
@@ -13,6 +14,7 @@ impl PicRepo {
     }
 
     pub async fn save_pic(&self, pic: Pic) -> Result<(), Error> {
+        let _timer = query_timer("pic.save_pic");
         sqlx::query!(
             "INSERT INTO pic (file_name, for_accept, data) VALUES (?, ?, ?)",
             pic.file_name,
@@ -25,6 +27,7 @@ impl PicRepo {
     }
 
     pub async fn delete_pic(&self, file_name: String, for_accept: bool) -> Result<(), Error> {
+        let _timer = query_timer("pic.delete_pic");
         sqlx::query!(
             "DELETE FROM pic WHERE (file_name, for_accept) IN
             (SELECT file_name, for_accept FROM pic WHERE file_name == ? AND for_accept == ?)",
@@ -37,6 +40,7 @@ impl PicRepo {
     }
 
     pub async fn get_all_pics(&self) -> Result<Vec<Pic>, Error> {
+        let _timer = query_timer("pic.get_all_pics");
         Ok(
             sqlx::query_as!(Pic, "SELECT file_name, for_accept, data FROM pic")
                 .fetch_all(&self.pool)
@@ -45,6 +49,7 @@ impl PicRepo {
     }
 
     pub async fn get_pic(&self, file_name: String, for_accept: bool) -> Result<Pic, Error> {
+        let _timer = query_timer("pic.get_pic");
         Ok(sqlx::query_as!(
             Pic,
             "SELECT file_name, for_accept, data FROM pic WHERE file_name = ? AND for_accept = ?",
@@ -56,6 +61,7 @@ impl PicRepo {
     }
 
     pub async fn get_random_pic(&self, for_accept: bool) -> Result<Pic, Error> {
+        let _timer = query_timer("pic.get_random_pic");
         Ok(sqlx::query_as!(
             Pic,
             "SELECT file_name, for_accept, data FROM pic WHERE for_accept = ? ORDER BY RANDOM() LIMIT 1",
@@ -0,0 +1,73 @@
+use std::convert::TryInto;
+
+use sqlx::{Error, Pool, Sqlite};
+
+use crate::data::model::relay_message::RelayMessage;
+use crate::metrics::query_timer;
+
+#[derive(Clone)]
+pub struct RelayMessageRepo {
+    pool: Pool<Sqlite>,
+}
+
+impl RelayMessageRepo {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        RelayMessageRepo { pool }
+    }
+
+    pub async fn save_relay_message(&self, relay_message: RelayMessage) -> Result<(), Error> {
+        let _timer = query_timer("relay_message.save_relay_message");
+        sqlx::query!(
+            "INSERT INTO relay_message (admin_chat_id, admin_message_id, user_chat_id, user_message_id) VALUES (?, ?, ?, ?)",
+            relay_message.admin_chat_id,
+            relay_message.admin_message_id,
+            relay_message.user_chat_id,
+            relay_message.user_message_id,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_by_admin_message(
+        &self,
+        admin_chat_id: i64,
+        admin_message_id: i32,
+    ) -> Result<RelayMessage, Error> {
+        let _timer = query_timer("relay_message.get_by_admin_message");
+        let result = sqlx::query!(
+            "SELECT * FROM relay_message WHERE admin_chat_id = ? AND admin_message_id = ? ORDER BY id DESC LIMIT 1",
+            admin_chat_id,
+            admin_message_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(RelayMessage::new(
+            result.admin_chat_id,
+            result.admin_message_id.try_into().unwrap(),
+            result.user_chat_id,
+            result.user_message_id.try_into().unwrap(),
+        ))
+    }
+
+    pub async fn get_by_user_message(
+        &self,
+        user_chat_id: i64,
+        user_message_id: i32,
+    ) -> Result<RelayMessage, Error> {
+        let _timer = query_timer("relay_message.get_by_user_message");
+        let result = sqlx::query!(
+            "SELECT * FROM relay_message WHERE user_chat_id = ? AND user_message_id = ? ORDER BY id DESC LIMIT 1",
+            user_chat_id,
+            user_message_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(RelayMessage::new(
+            result.admin_chat_id,
+            result.admin_message_id.try_into().unwrap(),
+            result.user_chat_id,
+            result.user_message_id.try_into().unwrap(),
+        ))
+    }
+}
@@ -1,5 +1,6 @@
 use crate::data::model::stats::UserStats;
-use sqlx::{Error, Pool, Sqlite};
+use crate::metrics::query_timer;
+use sqlx::{Error, Pool, Sqlite, Transaction};
 
 #[derive(Clone)]
 pub struct StatsRepo {
@@ -12,6 +13,7 @@ impl StatsRepo {
     }
 
     pub async fn get_stat_for_user(&self, user_id: i64) -> Option<UserStats> {
+        let _timer = query_timer("stats.get_stat_for_user");
         sqlx::query_as!(
             UserStats,
             "SELECT * FROM user_stats WHERE user_id = ?",
@@ -28,19 +30,25 @@ impl StatsRepo {
             .unwrap_or_else(|| UserStats::empty(user_id))
     }
 
-    pub async fn increment_offered(&self, user_id: i64) -> Result<(), Error> {
+    pub async fn increment_offered(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        user_id: i64,
+    ) -> Result<(), Error> {
+        let _timer = query_timer("stats.increment_offered");
         sqlx::query!(
             "INSERT INTO user_stats
 VALUES (?, 1, 0, 0)
 ON CONFLICT (user_id) DO UPDATE SET offered_count = offered_count + 1",
             user_id
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
         Ok(())
     }
 
     pub async fn increment_accepted(&self, user_id: i64) -> Result<(), Error> {
+        let _timer = query_timer("stats.increment_accepted");
         sqlx::query!(
             "INSERT INTO user_stats
 VALUES (?, 1, 1, 0)
@@ -53,6 +61,7 @@ ON CONFLICT (user_id) DO UPDATE SET accepted_count = accepted_count + 1",
     }
 
     pub async fn increment_declined(&self, user_id: i64) -> Result<(), Error> {
+        let _timer = query_timer("stats.increment_declined");
         sqlx::query!(
             "INSERT INTO user_stats
 VALUES (?, 1, 0, 1)
@@ -63,4 +72,15 @@ ON CONFLICT (user_id) DO UPDATE SET declined_count = declined_count + 1",
         .await?;
         Ok(())
     }
+
+    pub async fn decrement_accepted(&self, user_id: i64) -> Result<(), Error> {
+        let _timer = query_timer("stats.decrement_accepted");
+        sqlx::query!(
+            "UPDATE user_stats SET accepted_count = MAX(accepted_count - 1, 0) WHERE user_id = ?",
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
 }
@@ -0,0 +1,85 @@
+use sqlx::{Error, Pool, Sqlite};
+
+use crate::data::model::user_settings::UserSettings;
+use crate::metrics::query_timer;
+
+#[derive(Clone)]
+pub struct UserSettingsRepo {
+    pool: Pool<Sqlite>,
+}
+
+impl UserSettingsRepo {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        UserSettingsRepo { pool }
+    }
+
+    pub async fn get_settings(&self, chat_id: i64) -> Option<UserSettings> {
+        let _timer = query_timer("user_settings.get_settings");
+        sqlx::query_as!(
+            UserSettings,
+            "SELECT chat_id, credit, anonymous, anon_token FROM user_settings WHERE chat_id = ?",
+            chat_id
+        )
+        .fetch_one(&self.pool)
+        .await
+        .ok()
+    }
+
+    pub async fn get_settings_or_default(&self, chat_id: i64) -> UserSettings {
+        self.get_settings(chat_id)
+            .await
+            .unwrap_or_else(|| UserSettings::empty(chat_id))
+    }
+
+    pub async fn set_credit(&self, chat_id: i64, credit: bool) -> Result<(), Error> {
+        let _timer = query_timer("user_settings.set_credit");
+        sqlx::query!(
+            "INSERT INTO user_settings (chat_id, credit)
+VALUES (?, ?)
+ON CONFLICT (chat_id) DO UPDATE SET credit = excluded.credit",
+            chat_id,
+            credit
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_anonymous(&self, chat_id: i64, anonymous: bool) -> Result<(), Error> {
+        let _timer = query_timer("user_settings.set_anonymous");
+        sqlx::query!(
+            "INSERT INTO user_settings (chat_id, anonymous)
+VALUES (?, ?)
+ON CONFLICT (chat_id) DO UPDATE SET anonymous = excluded.anonymous",
+            chat_id,
+            anonymous
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_or_create_anon_token(
+        &self,
+        chat_id: i64,
+        new_token: String,
+    ) -> Result<String, Error> {
+        let _timer = query_timer("user_settings.get_or_create_anon_token");
+        sqlx::query!(
+            "INSERT INTO user_settings (chat_id, anon_token)
+VALUES (?, ?)
+ON CONFLICT (chat_id) DO UPDATE SET anon_token = COALESCE(anon_token, excluded.anon_token)",
+            chat_id,
+            new_token
+        )
+        .execute(&self.pool)
+        .await?;
+        let result = sqlx::query!(
+            "SELECT anon_token FROM user_settings WHERE chat_id = ?",
+            chat_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(result.anon_token.unwrap_or(new_token))
+    }
+}
@@ -0,0 +1,53 @@
+use sqlx::{Error, Pool, Sqlite};
+
+use crate::metrics::query_timer;
+
+#[derive(Clone)]
+pub struct VoteRepo {
+    pool: Pool<Sqlite>,
+}
+
+impl VoteRepo {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        VoteRepo { pool }
+    }
+
+    pub async fn save_vote(
+        &self,
+        admin_chat_id: i64,
+        admin_chat_message_id: i32,
+        voter_id: i64,
+        is_accept: bool,
+    ) -> Result<(), Error> {
+        let _timer = query_timer("vote.save_vote");
+        sqlx::query!(
+            "INSERT INTO vote (admin_chat_id, admin_chat_message_id, voter_id, is_accept)
+VALUES (?, ?, ?, ?)
+ON CONFLICT (admin_chat_id, admin_chat_message_id, voter_id) DO UPDATE SET is_accept = excluded.is_accept",
+            admin_chat_id,
+            admin_chat_message_id,
+            voter_id,
+            is_accept
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_tally(
+        &self,
+        admin_chat_id: i64,
+        admin_chat_message_id: i32,
+    ) -> Result<(i64, i64), Error> {
+        let _timer = query_timer("vote.get_tally");
+        let result = sqlx::query!(
+            r#"SELECT COALESCE(SUM(is_accept = 1), 0) AS "accepted!: i64", COALESCE(SUM(is_accept = 0), 0) AS "declined!: i64"
+FROM vote WHERE admin_chat_id = ? AND admin_chat_message_id = ?"#,
+            admin_chat_id,
+            admin_chat_message_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok((result.accepted, result.declined))
+    }
+}
@@ -0,0 +1,86 @@
+use std::convert::TryInto;
+
+use sqlx::{Error, Pool, Sqlite};
+
+use crate::data::model::webhook_event::WebhookEvent;
+use crate::metrics::query_timer;
+
+#[derive(Clone)]
+pub struct WebhookEventRepo {
+    pool: Pool<Sqlite>,
+}
+
+impl WebhookEventRepo {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        WebhookEventRepo { pool }
+    }
+
+    pub async fn enqueue(
+        &self,
+        url: &str,
+        event: &str,
+        payload: &str,
+        next_attempt_at: i64,
+    ) -> Result<(), Error> {
+        let _timer = query_timer("webhook_event.enqueue");
+        sqlx::query!(
+            "INSERT INTO webhook_event (url, event, payload, next_attempt_at) VALUES (?, ?, ?, ?)",
+            url,
+            event,
+            payload,
+            next_attempt_at
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_due(&self, now: i64, limit: i64) -> Result<Vec<WebhookEvent>, Error> {
+        let _timer = query_timer("webhook_event.get_due");
+        let result = sqlx::query!(
+            "SELECT id, url, event, payload, attempts, next_attempt_at FROM webhook_event
+WHERE next_attempt_at <= ? ORDER BY id LIMIT ?",
+            now,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result
+            .into_iter()
+            .map(|row| WebhookEvent {
+                id: row.id,
+                url: row.url,
+                event: row.event,
+                payload: row.payload,
+                attempts: row.attempts.try_into().unwrap(),
+                next_attempt_at: row.next_attempt_at,
+            })
+            .collect())
+    }
+
+    pub async fn reschedule(
+        &self,
+        id: i64,
+        attempts: i32,
+        next_attempt_at: i64,
+    ) -> Result<(), Error> {
+        let _timer = query_timer("webhook_event.reschedule");
+        sqlx::query!(
+            "UPDATE webhook_event SET attempts = ?, next_attempt_at = ? WHERE id = ?",
+            attempts,
+            next_attempt_at,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, id: i64) -> Result<(), Error> {
+        let _timer = query_timer("webhook_event.delete");
+        sqlx::query!("DELETE FROM webhook_event WHERE id = ?", id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+use std::env;
+use std::fs;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Deserialize;
+use teloxide::types::{Message, MessageEntity, MessageEntityKind};
+use url::Url;
+
+use crate::utils::result_utils::FatalValueMapper;
+
+static FILTERS_FILE_KEY: &str = "FILTERS_FILE";
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterAction {
+    Reject,
+    Ban,
+    Flag,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum FilterCondition {
+    Regex { pattern: String },
+    DomainDeny { domains: Vec<String> },
+    DomainAllow { domains: Vec<String> },
+    MaxCaptionLength { length: usize },
+    ForwardedFromChannel,
+}
+
+#[derive(Deserialize)]
+struct FilterRuleConfig {
+    #[serde(flatten)]
+    condition: FilterCondition,
+    action: FilterAction,
+    message: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FiltersConfig {
+    rules: Vec<FilterRuleConfig>,
+}
+
+enum Condition {
+    Regex(Regex),
+    DomainDeny(Vec<String>),
+    DomainAllow(Vec<String>),
+    MaxCaptionLength(usize),
+    ForwardedFromChannel,
+}
+
+struct FilterRule {
+    condition: Condition,
+    action: FilterAction,
+    message: Option<String>,
+}
+
+pub struct FilterHit {
+    pub action: FilterAction,
+    pub reason: String,
+    pub message: Option<String>,
+}
+
+lazy_static! {
+    static ref FILTERS: Vec<FilterRule> = load_filters();
+}
+
+fn load_filters() -> Vec<FilterRule> {
+    let path = match env::var(FILTERS_FILE_KEY) {
+        Ok(path) => path,
+        Err(_) => return Vec::new(),
+    };
+    let content =
+        fs::read_to_string(&path).map_value_or_exit(format!("Can not read filters file {}", path));
+    let config: FiltersConfig = serde_json::from_str(&content)
+        .map_value_or_exit(format!("Can not parse filters file {}", path));
+    config
+        .rules
+        .into_iter()
+        .map(|rule| FilterRule {
+            condition: match rule.condition {
+                FilterCondition::Regex { pattern } => Condition::Regex(
+                    Regex::new(&pattern)
+                        .map_value_or_exit(format!("Invalid filter regex {}", pattern)),
+                ),
+                FilterCondition::DomainDeny { domains } => {
+                    Condition::DomainDeny(normalize_domains(domains))
+                }
+                FilterCondition::DomainAllow { domains } => {
+                    Condition::DomainAllow(normalize_domains(domains))
+                }
+                FilterCondition::MaxCaptionLength { length } => Condition::MaxCaptionLength(length),
+                FilterCondition::ForwardedFromChannel => Condition::ForwardedFromChannel,
+            },
+            action: rule.action,
+            message: rule.message,
+        })
+        .collect()
+}
+
+pub fn check_message(message: &Message) -> Vec<FilterHit> {
+    let text = message.text().or_else(|| message.caption()).unwrap_or("");
+    let hosts = extract_hosts(message);
+    FILTERS
+        .iter()
+        .filter_map(|rule| {
+            let reason = match &rule.condition {
+                Condition::Regex(regex) => regex
+                    .find(text)
+                    .map(|found| format!("matched \"{}\"", found.as_str())),
+                Condition::DomainDeny(domains) => hosts
+                    .iter()
+                    .find(|host| matches_any(host, domains))
+                    .map(|host| format!("link to {}", host)),
+                Condition::DomainAllow(domains) => hosts
+                    .iter()
+                    .find(|host| !matches_any(host, domains))
+                    .map(|host| format!("link to {}", host)),
+                Condition::MaxCaptionLength(length) => {
+                    let count = text.chars().count();
+                    (count > *length).then(|| format!("text is {} chars long", count))
+                }
+                Condition::ForwardedFromChannel => message
+                    .forward_from_chat()
+                    .filter(|chat| chat.is_channel())
+                    .map(|_| "forwarded from a channel".to_string()),
+            }?;
+            Some(FilterHit {
+                action: rule.action,
+                reason,
+                message: rule.message.clone(),
+            })
+        })
+        .collect()
+}
+
+fn normalize_domains(domains: Vec<String>) -> Vec<String> {
+    domains
+        .into_iter()
+        .map(|domain| domain.trim().trim_start_matches('.').to_lowercase())
+        .collect()
+}
+
+fn matches_any(host: &str, domains: &[String]) -> bool {
+    domains
+        .iter()
+        .any(|domain| host == domain || host.ends_with(&format!(".{}", domain)))
+}
+
+fn extract_hosts(message: &Message) -> Vec<String> {
+    let (text, entities) = match (message.text(), message.entities()) {
+        (Some(text), entities) => (text, entities.unwrap_or(&[])),
+        (None, _) => (
+            message.caption().unwrap_or(""),
+            message.caption_entities().unwrap_or(&[]),
+        ),
+    };
+    entities
+        .iter()
+        .filter_map(|entity| match &entity.kind {
+            MessageEntityKind::Url => Some(entity_text(text, entity)),
+            MessageEntityKind::TextLink { url } => Some(url.to_string()),
+            _ => None,
+        })
+        .filter_map(|link| {
+            let link = if link.contains("://") {
+                link
+            } else {
+                format!("http://{}", link)
+            };
+            Url::parse(&link)
+                .ok()
+                .and_then(|url| url.host_str().map(|host| host.to_lowercase()))
+        })
+        .collect()
+}
+
+fn entity_text(text: &str, entity: &MessageEntity) -> String {
+    let utf16 = text
+        .encode_utf16()
+        .skip(entity.offset)
+        .take(entity.length)
+        .collect::<Vec<u16>>();
+    String::from_utf16_lossy(&utf16)
+}
@@ -1,34 +1,74 @@
 extern crate dotenv;
 
+use chrono::{Local, Utc};
 use dotenv::dotenv;
 use lazy_static::lazy_static;
-use log::warn;
+use log::{info, warn};
+use serde_json::json;
+use std::collections::HashMap;
 use std::env;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
 use teloxide::prelude::*;
-use teloxide::types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup, InputFile};
+use teloxide::types::{
+    ChatId, Document, InlineKeyboardButton, InlineKeyboardMarkup, InputFile, ParseMode, PhotoSize,
+    User,
+};
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
-use crate::admin_commands::exec_command;
+use crate::admin_commands::{exec_command, pending_callback, set_ban, PENDING_PAGE_CALLBACK};
+use crate::api::{start_admin_api, ApiState};
+use crate::crosspost::{is_crosspost_enabled, start_crosspost, CrosspostPayload};
 use crate::data::db::{create_database_if_needed, create_pool, migrate};
 use crate::data::model::cached_pic::CachedPic;
-use crate::data::model::offered_post::OfferedPost;
-use crate::data::model::stats::UserStats;
-use crate::data::repo::cached_pic_repo::CachedPicRepo;
-use crate::data::repo::ban_repo::BanRepo;
+use crate::data::model::offered_post::{OfferedPost, OfferedPostStatus};
 use crate::data::repo::offered_post_repo::OfferedPostRepo;
-use crate::data::repo::pic_repo::PicRepo;
-use crate::data::repo::stats_repo::StatsRepo;
-use crate::utils::document_utils::download_doc_vec;
+use crate::data::repo::vote_repo::VoteRepo;
+use crate::data::repo::Repos;
+use crate::filters::{check_message, FilterAction, FilterHit};
+use crate::metrics::{
+    inc_counter, start_metrics_server, CALLBACK_ERRORS, SUGGESTIONS_DECIDED, SUGGESTIONS_RECEIVED,
+};
+use crate::moderation::{moderate, Verdict};
+use crate::relay::{relay_to_admins, relay_to_user};
+use crate::scheduler::start_scheduler;
+use crate::user_commands::{exec_user_command, withdraw, WITHDRAW_CALLBACK};
+use crate::utils::anon_utils::{generate_anon_token, is_anonymous};
+use crate::utils::caption_utils::{
+    is_caption_transformed, transform_caption, Caption, CAPTION_LIMIT, TEXT_LIMIT,
+};
+use crate::utils::credit_utils::{get_credit, Credit};
+use crate::utils::document_utils::{
+    download_doc_file, download_photo_vec, TempFile, PHOTO_UPLOAD_LIMIT,
+};
 use crate::utils::env_utils::get_env_key;
 use crate::utils::error_utils::HandlerError;
+use crate::utils::html_utils::entities_to_html;
+use crate::utils::image_utils::{init_media_processing, process_image, MEDIA_PROCESSING};
+use crate::utils::link_utils::channel_post_link;
 use crate::utils::message_utils::ExtMessage;
-use crate::utils::mime_utils::{is_animate, is_image, is_video};
+use crate::utils::mime_utils::{format_from_mime, needs_sniffing, sniff_format, MediaType};
 use crate::utils::pic_utils::{get_pic, GetPicResult};
+use crate::utils::request_utils::SendRetry;
 use crate::utils::user_utils::ExtUser;
+use crate::utils::video_utils::{gif_to_mp4, should_transcode_gif};
+use crate::webhooks::{emit, start_webhook_dispatcher, WebhookEventKind};
 
 mod admin_commands;
+mod api;
+mod crosspost;
 mod data;
+mod filters;
+mod metrics;
+mod moderation;
+mod relay;
+mod scheduler;
+mod user_commands;
 mod utils;
+mod webhooks;
 
 static CHANNEL_ID_KEY: &str = "CHANNEL_ID";
 static ADMINS_CHAT_ID_KEY: &str = "ADMINS_CHAT_ID";
@@ -38,280 +78,845 @@ static ACCEPT_CALLBACK: &str = "accept";
 static DECLINE_CALLBACK: &str = "decline";
 static SILENT_DECLINE_CALLBACK: &str = "decline-silent";
 static WITHOUT_TEXT_CALLBACK: &str = "accept-without-text";
+static VOTE_UP_CALLBACK: &str = "vote-up";
+static VOTE_DOWN_CALLBACK: &str = "vote-down";
+static UNDO_CALLBACK: &str = "undo";
 
-const STATS_COMMAND: &str = "/stats";
+static VOTING_QUORUM_KEY: &str = "VOTING_QUORUM";
+static VOTING_OWNERS_KEY: &str = "VOTING_OWNERS";
+static UNDO_TIMEOUT_KEY: &str = "UNDO_TIMEOUT";
+static KEEP_HISTORY_KEY: &str = "KEEP_HISTORY";
 
 lazy_static! {
     static ref CHANNEL_ID: String = get_env_key(CHANNEL_ID_KEY);
     static ref ADMINS_CHAT_ID: String = get_env_key(ADMINS_CHAT_ID_KEY);
     static ref TELOXIDE_TOKEN: String = get_env_key(TELOXIDE_TOKEN_KEY);
+    static ref VOTING_QUORUM: i64 = env::var(VOTING_QUORUM_KEY)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0);
+    static ref VOTING_OWNERS: Vec<i64> = env::var(VOTING_OWNERS_KEY)
+        .unwrap_or(String::new())
+        .split(',')
+        .filter_map(|id| id.trim().parse().ok())
+        .collect();
+    static ref UNDO_TIMEOUT: u64 = env::var(UNDO_TIMEOUT_KEY)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0);
+    static ref KEEP_HISTORY: bool = env::var(KEEP_HISTORY_KEY)
+        .map(|value| value == "true" || value == "1")
+        .unwrap_or(false);
+    static ref PENDING_DECISIONS: Mutex<HashMap<(i64, i32), JoinHandle<()>>> =
+        Mutex::new(HashMap::new());
 }
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
     teloxide::enable_logging!();
+    init_media_processing();
     create_database_if_needed().await;
     let pool = create_pool().await;
     migrate(&pool).await;
-    let offered_post_repo = OfferedPostRepo::new(pool.clone());
-    let message_handler_repo = offered_post_repo.clone();
-    let queries_handler_repo = offered_post_repo.clone();
-    let cached_pic_repo = CachedPicRepo::new(pool.clone());
-    let ban_repo = BanRepo::new(pool.clone());
-    let pic_repo = PicRepo::new(pool.clone());
-    let message_handler_pic_repo = pic_repo.clone();
-    let queries_handler_pic_repo = pic_repo.clone();
-    let stats_repo = StatsRepo::new(pool.clone());
-    let message_handler_stats_repo = stats_repo.clone();
-    let queries_handler_stats_repo = stats_repo.clone();
+    let repos = Repos::new(pool);
+    if let Err(e) = repos.offered_post_repo.release_all_decisions().await {
+        warn!(
+            "Can not release interrupted decisions, due to error {:?}",
+            e
+        );
+    }
+    let message_handler_repos = repos.clone();
+    let edited_message_handler_repos = repos.clone();
+    let queries_handler_repos = repos.clone();
+    let bot = Bot::new(TELOXIDE_TOKEN.to_string());
+    rearm_decisions(&bot, &repos).await;
+    start_scheduler(
+        bot.clone(),
+        repos.offered_post_repo.clone(),
+        repos.stats_repo.clone(),
+        repos.webhook_event_repo.clone(),
+    );
+    start_webhook_dispatcher(repos.webhook_event_repo.clone());
+    start_metrics_server(repos.offered_post_repo.clone());
+    start_admin_api(ApiState {
+        bot: bot.clone(),
+        repos,
+    });
     log::info!("Bot is running.");
-    Dispatcher::new(Bot::new(TELOXIDE_TOKEN.to_string()))
+    Dispatcher::new(bot)
         .messages_handler(|rx: DispatcherHandlerRx<Bot, Message>| {
             UnboundedReceiverStream::new(rx).for_each_concurrent(None, move |cx| {
-                let offered_post_repo = message_handler_repo.clone();
-                let pic_repo = message_handler_pic_repo.clone();
-                let stats_repo = message_handler_stats_repo.clone();
-                let ban_repo = ban_repo.clone();
+                let repos = message_handler_repos.clone();
                 async move {
-                    match message_handler(cx, &offered_post_repo, &pic_repo, &stats_repo, &ban_repo).await {
+                    match message_handler(cx, &repos).await {
                         Ok(_) => {}
                         Err(err) => log::warn!("{}", err),
                     }
                 }
             })
         })
-        .callback_queries_handler(|rx: DispatcherHandlerRx<Bot, CallbackQuery>| {
+        .edited_messages_handler(|rx: DispatcherHandlerRx<Bot, Message>| {
             UnboundedReceiverStream::new(rx).for_each_concurrent(None, move |cx| {
-                let offered_post_repo = queries_handler_repo.clone();
-                let cached_pic_repo = cached_pic_repo.clone();
-                let pic_repo = queries_handler_pic_repo.clone();
-                let stats_repo = queries_handler_stats_repo.clone();
+                let repos = edited_message_handler_repos.clone();
                 async move {
-                    match callback_handler(
-                        cx,
-                        &offered_post_repo,
-                        &cached_pic_repo,
-                        &pic_repo,
-                        &stats_repo,
-                    )
-                    .await
-                    {
+                    match edited_message_handler(cx, &repos).await {
                         Ok(_) => {}
                         Err(err) => log::warn!("{}", err),
                     }
                 }
             })
         })
+        .callback_queries_handler(|rx: DispatcherHandlerRx<Bot, CallbackQuery>| {
+            UnboundedReceiverStream::new(rx).for_each_concurrent(None, move |cx| {
+                let repos = queries_handler_repos.clone();
+                async move {
+                    let bot = cx.requester.clone();
+                    let query_id = cx.update.id.to_string();
+                    match callback_handler(cx, &repos).await {
+                        Ok(_) => {}
+                        Err(err) => {
+                            inc_counter(CALLBACK_ERRORS, &[]);
+                            log::warn!("{}", err);
+                            let _ = bot
+                                .answer_callback_query(query_id)
+                                .text(err.user_message())
+                                .send_retry()
+                                .await;
+                        }
+                    }
+                }
+            })
+        })
         .dispatch()
         .await;
 }
 
 async fn message_handler(
     cx: UpdateWithCx<Bot, Message>,
-    offered_post_repo: &OfferedPostRepo,
-    pic_repo: &PicRepo,
-    stats_repo: &StatsRepo,
-    ban_repo: &BanRepo,
+    repos: &Repos,
 ) -> Result<(), HandlerError> {
     if cx.update.chat.id.to_string() == ADMINS_CHAT_ID.to_string() {
         if let Some(text) = cx.update.text().or_else(|| cx.update.caption()) {
-            exec_command(text, &cx, pic_repo, offered_post_repo, &ban_repo).await?;
+            if text.starts_with("/") {
+                exec_command(text, &cx, repos).await?;
+                return Ok(());
+            }
         }
+        relay_to_user(&cx, &repos.relay_message_repo).await?;
         return Ok(());
     }
-    if let Ok(is_banned) = &ban_repo.is_banned(cx.update.chat_id()).await {
+    if let Ok(is_banned) = &repos.ban_repo.is_banned(cx.update.chat_id()).await {
         if *is_banned {
             return Ok(());
         }
     }
     if let Some(text) = cx.update.text() {
         if text.starts_with("/") {
-            return match text {
-                STATS_COMMAND => {
-                    let UserStats {
-                        offered_count,
-                        accepted_count,
-                        declined_count,
-                        ..
-                    } = stats_repo
-                        .get_stat_for_user_or_default(cx.update.chat_id())
-                        .await;
-                    let res = cx
-                        .reply_to(format!(
-                            "Your stats are:\nOffered: {}\nAccepted: {}\nDeclined: {}",
-                            offered_count, accepted_count, declined_count
-                        ))
-                        .send()
-                        .await;
-                    res.log_on_error().await;
-                    Ok(())
-                }
-                &_ => Ok(()),
-            };
+            return exec_user_command(
+                text,
+                &cx,
+                &repos.stats_repo,
+                &repos.offered_post_repo,
+                &repos.user_settings_repo,
+            )
+            .await;
         }
     }
+    if relay_to_admins(&cx, &repos.relay_message_repo, ADMINS_CHAT_ID.as_str()).await? {
+        return Ok(());
+    }
 
-    let _mes = cx.forward_to(ADMINS_CHAT_ID.to_string()).send().await?;
     let user = cx
         .update
         .from()
-        .ok_or(HandlerError::from_str("User not found"))?;
-    let message = cx
-        .requester
-        .send_message(
-            ADMINS_CHAT_ID.to_string(),
-            format!("From: {}\nWe going to shitpost it?", user.ftm_title(),),
+        .ok_or(HandlerError::missing("User not found"))?;
+    let anonymous = is_anonymous(
+        repos
+            .user_settings_repo
+            .get_settings_or_default(cx.update.chat_id())
+            .await
+            .anonymous,
+    );
+    let submitter = if anonymous {
+        repos
+            .user_settings_repo
+            .get_or_create_anon_token(cx.update.chat_id(), generate_anon_token())
+            .await?
+    } else {
+        user.ftm_title()
+    };
+    let hits = check_message(&cx.update);
+    if enforce_filters(&cx, &submitter, &hits, repos).await? {
+        return Ok(());
+    }
+    let mut notes = flag_notes(&hits);
+    if let Some(result) = moderate(&cx.requester, &cx.update).await {
+        if result.verdict == Verdict::Reject {
+            cx.reply_to("😕 This suggestion can not be accepted.")
+                .send_retry()
+                .await?;
+            return Ok(());
+        }
+        notes.push(result.note());
+    }
+    let (_mes, message) = offer_to_admins(
+        &cx.requester,
+        &cx.update,
+        &submitter,
+        anonymous,
+        false,
+        &notes,
+    )
+    .await?;
+    let saved = save_submission(
+        repos,
+        OfferedPost {
+            author: if anonymous {
+                None
+            } else {
+                Some(user.credit_title())
+            },
+            is_anonymous: anonymous,
+            kind: Some(cx.update.kind_name().to_string()),
+            ..OfferedPost::new(
+                cx.update.chat_id(),
+                cx.update.id,
+                message.chat.id,
+                message.id,
+                Some(_mes),
+                cx.update.date as i64,
+            )
+        },
+        submitter.to_string(),
+    )
+    .await;
+    if let Err(e) = saved {
+        retract_offer(&cx.requester, message.chat.id, message.id, Some(_mes)).await;
+        let _ = cx
+            .reply_to("😕 Can not save the suggestion, please try again later.")
+            .send_retry()
+            .await;
+        return Err(e.into());
+    }
+    inc_counter(SUGGESTIONS_RECEIVED, &[("kind", cx.update.kind_name())]);
+    let mut event = json!({
+        "author": if anonymous { None } else { Some(user.credit_title()) },
+        "anonymous": anonymous,
+        "kind": cx.update.kind_name(),
+    });
+    if !anonymous {
+        event["chat_id"] = json!(cx.update.chat_id());
+        event["message_id"] = json!(cx.update.id);
+    }
+    emit(
+        &repos.webhook_event_repo,
+        WebhookEventKind::SuggestionReceived,
+        event,
+    )
+    .await;
+    Ok(())
+}
+
+async fn save_submission(
+    repos: &Repos,
+    offered_post: OfferedPost,
+    submitter: String,
+) -> Result<(), sqlx::Error> {
+    let chat_id = offered_post.chat_id;
+    let date = offered_post.created_at.to_string();
+    let mut tx = repos.offered_post_repo.begin().await?;
+    repos
+        .offered_post_repo
+        .save_offered_post(&mut tx, offered_post)
+        .await?;
+    repos
+        .ban_repo
+        .create_in(&mut tx, chat_id, submitter, date)
+        .await?;
+    repos.stats_repo.increment_offered(&mut tx, chat_id).await?;
+    tx.commit().await
+}
+
+async fn enforce_filters(
+    cx: &UpdateWithCx<Bot, Message>,
+    submitter: &str,
+    hits: &[FilterHit],
+    repos: &Repos,
+) -> Result<bool, HandlerError> {
+    if let Some(hit) = hits.iter().find(|hit| hit.action == FilterAction::Ban) {
+        set_ban(
+            &repos.ban_repo,
+            &repos.webhook_event_repo,
+            cx.update.chat_id(),
+            submitter.to_string(),
+            cx.update.date.to_string(),
+            true,
         )
-        .reply_to_message_id(_mes.id)
-        .reply_markup(build_keyboard(
-            cx.update.has_caption(),
-            cx.update.text().is_some(),
-        ))
-        .send()
         .await?;
-    let _ = offered_post_repo
-        .save_offered_post(OfferedPost::new(
+        info!(
+            "User {} banned by content filter: {}",
             cx.update.chat_id(),
-            cx.update.id,
-            message.chat.id,
-            message.id,
-            Some(_mes.id),
-        ))
-        .await;
-    let _ = ban_repo
-        .create(cx.update.chat_id(), user.ftm_title(), cx.update.date.to_string())
-        .await;
-    match stats_repo.increment_offered(cx.update.chat_id()).await {
-        Err(e) => {
-            warn!(
-                "Can not increment offered post by user {} - id {}, due to error {:?}",
-                cx.update
-                    .from()
-                    .map_or("UNKNOWN".to_string(), |x| x.ftm_title()),
-                cx.update.chat_id(),
-                e
-            )
-        }
-        _ => {}
+            hit.reason
+        );
+        return Ok(true);
+    }
+    if let Some(hit) = hits.iter().find(|hit| hit.action == FilterAction::Reject) {
+        cx.reply_to(
+            hit.message
+                .as_deref()
+                .unwrap_or("😕 This suggestion can not be accepted."),
+        )
+        .send_retry()
+        .await?;
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+async fn edited_message_handler(
+    cx: UpdateWithCx<Bot, Message>,
+    repos: &Repos,
+) -> Result<(), HandlerError> {
+    if cx.update.chat.id.to_string() == ADMINS_CHAT_ID.to_string() {
+        return Ok(());
+    }
+    let post = match repos
+        .offered_post_repo
+        .get_user_offered_post(cx.update.chat_id(), cx.update.id)
+        .await
+    {
+        Ok(post) if post.status == OfferedPostStatus::Pending => post,
+        _ => return Ok(()),
+    };
+    let user = cx
+        .update
+        .from()
+        .ok_or(HandlerError::missing("User not found"))?;
+    let submitter = if post.is_anonymous {
+        repos
+            .user_settings_repo
+            .get_or_create_anon_token(cx.update.chat_id(), generate_anon_token())
+            .await?
+    } else {
+        user.ftm_title()
     };
+    let hits = check_message(&cx.update);
+    if enforce_filters(&cx, &submitter, &hits, repos).await? {
+        return decline_edited(&cx.requester, &post, &repos.offered_post_repo).await;
+    }
+    let mut notes = flag_notes(&hits);
+    if let Some(result) = moderate(&cx.requester, &cx.update).await {
+        if result.verdict == Verdict::Reject {
+            cx.reply_to("😕 This suggestion can not be accepted.")
+                .send_retry()
+                .await?;
+            return decline_edited(&cx.requester, &post, &repos.offered_post_repo).await;
+        }
+        notes.push(result.note());
+    }
+    let (forwarded, keyboard) = offer_to_admins(
+        &cx.requester,
+        &cx.update,
+        &submitter,
+        post.is_anonymous,
+        true,
+        &notes,
+    )
+    .await?;
+    cancel_pending_decision(post.admin_chat_id, post.admin_chat_message_id);
+    if let Err(e) = repos
+        .offered_post_repo
+        .update_admin_messages(post.chat_id, post.message_id, keyboard.id, Some(forwarded))
+        .await
+    {
+        retract_offer(
+            &cx.requester,
+            keyboard.chat.id,
+            keyboard.id,
+            Some(forwarded),
+        )
+        .await;
+        return Err(e.into());
+    }
+    retract_offer(
+        &cx.requester,
+        post.admin_chat_id,
+        post.admin_chat_message_id,
+        post.admin_chat_additional_message_id,
+    )
+    .await;
+    Ok(())
+}
+
+async fn decline_edited(
+    bot: &Bot,
+    post: &OfferedPost,
+    offered_post_repo: &OfferedPostRepo,
+) -> Result<(), HandlerError> {
+    cancel_pending_decision(post.admin_chat_id, post.admin_chat_message_id);
+    offered_post_repo
+        .update_status(
+            post.admin_chat_id,
+            post.admin_chat_message_id,
+            OfferedPostStatus::Declined,
+        )
+        .await?;
+    retract_offer(
+        bot,
+        post.admin_chat_id,
+        post.admin_chat_message_id,
+        post.admin_chat_additional_message_id,
+    )
+    .await;
     Ok(())
 }
 
 async fn callback_handler(
     cx: UpdateWithCx<Bot, CallbackQuery>,
-    offered_post_repo: &OfferedPostRepo,
-    cached_pic_repo: &CachedPicRepo,
-    pic_repo: &PicRepo,
-    stats_repo: &StatsRepo,
+    repos: &Repos,
 ) -> Result<(), HandlerError> {
     let data = cx
         .update
         .data
         .clone()
-        .ok_or(HandlerError::from_str("Data not found"))?;
+        .ok_or(HandlerError::missing("Data not found"))?;
+    if data.starts_with(WITHDRAW_CALLBACK) {
+        return withdraw(&cx, &data, &repos.offered_post_repo).await;
+    }
+    if data.starts_with(PENDING_PAGE_CALLBACK) {
+        return pending_callback(&cx, &data, &repos.offered_post_repo).await;
+    }
     let message = cx
         .update
         .message
         .as_ref()
-        .ok_or(HandlerError::from_str("Message not found"))?;
+        .ok_or(HandlerError::missing("Message not found"))?;
     let origin = message
         .reply_to_message()
-        .ok_or(HandlerError::from_str("Reply message are missing"))?;
-    let is_accept = data.starts_with(ACCEPT_CALLBACK);
-    if is_accept {
-        if let Some(doc) = origin.document() {
-            if is_image(doc) {
-                if let Some(image) = download_doc_vec(doc, &cx.requester).await {
-                    let r = cx.requester.send_photo(
-                        CHANNEL_ID.to_string(),
-                        InputFile::memory("image.png", image),
-                    );
-                    if origin.has_caption() && !data.starts_with(WITHOUT_TEXT_CALLBACK) {
-                        r.caption(origin.caption().unwrap().to_string())
-                            .send()
-                            .await?;
-                    } else {
-                        r.send().await?;
-                    }
-                } else {
-                    simple_copy(&cx, &data, &message, origin).await?
-                }
-            } else if is_animate(doc) {
-                if let Some(image) = download_doc_vec(doc, &cx.requester).await {
-                    let r = cx.requester.send_animation(
-                        CHANNEL_ID.to_string(),
-                        InputFile::memory("image.gif", image),
-                    );
-                    if origin.has_caption() && !data.starts_with(WITHOUT_TEXT_CALLBACK) {
-                        r.caption(origin.caption().unwrap().to_string())
-                            .send()
-                            .await?;
-                    } else {
-                        r.send().await?;
+        .ok_or(HandlerError::missing("Reply message are missing"))?;
+    if data == UNDO_CALLBACK {
+        return undo_decision(&cx, message, origin, repos).await;
+    }
+    let data = if *VOTING_QUORUM > 0 {
+        match vote(&cx, data, message, origin, &repos.vote_repo).await? {
+            Some(decision) => decision,
+            None => return Ok(()),
+        }
+    } else {
+        data
+    };
+    if *UNDO_TIMEOUT > 0 {
+        return schedule_decision(&cx, data, message, origin, repos).await;
+    }
+    apply_decision(
+        &cx.requester,
+        &data,
+        message,
+        origin,
+        &cx.update.from,
+        repos,
+    )
+    .await
+}
+
+async fn schedule_decision(
+    cx: &UpdateWithCx<Bot, CallbackQuery>,
+    data: String,
+    message: &Message,
+    origin: &Message,
+    repos: &Repos,
+) -> Result<(), HandlerError> {
+    let moderator = cx.update.from.clone();
+    repos
+        .offered_post_repo
+        .save_scheduled_decision(
+            message.chat_id(),
+            message.id,
+            &data,
+            moderator.ftm_title(),
+            Utc::now().timestamp() + *UNDO_TIMEOUT as i64,
+        )
+        .await?;
+    let edited = cx
+        .requester
+        .edit_message_reply_markup(message.chat_id(), message.id)
+        .reply_markup(InlineKeyboardMarkup::default().append_row(vec![
+            InlineKeyboardButton::callback(
+                format!("↩️ Undo ({}s)", *UNDO_TIMEOUT),
+                UNDO_CALLBACK.to_string(),
+            ),
+        ]))
+        .send_retry()
+        .await;
+    if let Err(e) = edited {
+        let _ = repos
+            .offered_post_repo
+            .clear_scheduled_decision(message.chat_id(), message.id)
+            .await;
+        return Err(e.into());
+    }
+    let bot = cx.requester.clone();
+    let message = message.clone();
+    let origin = origin.clone();
+    let repos = repos.clone();
+    track_decision(
+        (message.chat_id(), message.id),
+        Duration::from_secs(*UNDO_TIMEOUT),
+        async move {
+            run_scheduled_decision(&bot, &data, &message, &origin, &moderator, &repos).await;
+        },
+    );
+    cx.requester
+        .answer_callback_query(cx.update.id.to_string())
+        .text(format!("Decision will be applied in {}s.", *UNDO_TIMEOUT))
+        .send_retry()
+        .await?;
+    Ok(())
+}
+
+async fn rearm_decisions(bot: &Bot, repos: &Repos) {
+    let decisions = match repos.offered_post_repo.get_scheduled_decisions().await {
+        Ok(decisions) => decisions,
+        Err(e) => {
+            warn!("Can not load scheduled decisions, due to error {:?}", e);
+            return;
+        }
+    };
+    let now = Utc::now().timestamp();
+    for decision in decisions {
+        let bot = bot.clone();
+        let repos = repos.clone();
+        let key = (decision.admin_chat_id, decision.admin_chat_message_id);
+        let delay = Duration::from_secs((decision.due_at - now).max(0) as u64);
+        track_decision(key, delay, async move {
+            let moderator = User {
+                id: 0,
+                is_bot: false,
+                first_name: decision.moderator,
+                last_name: None,
+                username: None,
+                language_code: None,
+            };
+            let message = bot
+                .edit_message_reply_markup(key.0, key.1)
+                .send_retry()
+                .await;
+            match message {
+                Ok(message) => match message.reply_to_message() {
+                    Some(origin) => {
+                        run_scheduled_decision(
+                            &bot,
+                            &decision.data,
+                            &message,
+                            origin,
+                            &moderator,
+                            &repos,
+                        )
+                        .await
                     }
-                } else {
-                    simple_copy(&cx, &data, &message, origin).await?
-                }
-            } else if is_video(doc) {
-                if let Some(video) = download_doc_vec(doc, &cx.requester).await {
-                    let r = cx.requester.send_video(
-                        CHANNEL_ID.to_string(),
-                        InputFile::memory("image.mp4", video),
-                    );
-                    if origin.has_caption() && !data.starts_with(WITHOUT_TEXT_CALLBACK) {
-                        r.caption(origin.caption().unwrap().to_string())
-                            .send()
-                            .await?;
-                    } else {
-                        r.send().await?;
+                    None => {
+                        restore_keyboard(&bot, &message, None, None).await;
+                        let _ = repos
+                            .offered_post_repo
+                            .clear_scheduled_decision(key.0, key.1)
+                            .await;
                     }
-                } else {
-                    simple_copy(&cx, &data, &message, origin).await?
+                },
+                Err(e) => {
+                    warn!("Can not resume scheduled decision, due to error {:?}", e);
+                    let _ = repos
+                        .offered_post_repo
+                        .clear_scheduled_decision(key.0, key.1)
+                        .await;
                 }
-            } else {
-                simple_copy(&cx, &data, &message, origin).await?
             }
-        } else {
-            simple_copy(&cx, &data, &message, origin).await?
+        });
+    }
+}
+
+fn track_decision<F>(key: (i64, i32), delay: Duration, decision: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let mut pending_decisions = PENDING_DECISIONS.lock().unwrap();
+    let handle = tokio::spawn(async move {
+        sleep(delay).await;
+        if PENDING_DECISIONS.lock().unwrap().remove(&key).is_none() {
+            return;
+        }
+        decision.await;
+    });
+    pending_decisions.insert(key, handle);
+}
+
+async fn run_scheduled_decision(
+    bot: &Bot,
+    data: &String,
+    message: &Message,
+    origin: &Message,
+    moderator: &User,
+    repos: &Repos,
+) {
+    let result = apply_decision(bot, data, message, origin, moderator, repos).await;
+    let _ = repos
+        .offered_post_repo
+        .clear_scheduled_decision(message.chat_id(), message.id)
+        .await;
+    if let Err(err) = result {
+        log::warn!("{}", err);
+        if let HandlerError::Validation(_) = err {
+            return;
         }
+        restore_keyboard(bot, message, Some(origin), None).await;
+        let _ = bot
+            .send_message(
+                message.chat_id(),
+                format!(
+                    "{} {}, the decision is not applied, try again.",
+                    err.user_message(),
+                    moderator.ftm_title()
+                ),
+            )
+            .reply_to_message_id(message.id)
+            .send_retry()
+            .await;
     }
-    if !data.starts_with(SILENT_DECLINE_CALLBACK) {
-        let offered_post = offered_post_repo
-            .get_offered_post(message.chat_id(), message.id)
+}
+
+fn cancel_pending_decision(admin_chat_id: i64, admin_chat_message_id: i32) {
+    if let Some(handle) = PENDING_DECISIONS
+        .lock()
+        .unwrap()
+        .remove(&(admin_chat_id, admin_chat_message_id))
+    {
+        handle.abort();
+    }
+}
+
+async fn undo_decision(
+    cx: &UpdateWithCx<Bot, CallbackQuery>,
+    message: &Message,
+    origin: &Message,
+    repos: &Repos,
+) -> Result<(), HandlerError> {
+    let handle = PENDING_DECISIONS
+        .lock()
+        .unwrap()
+        .remove(&(message.chat_id(), message.id));
+    let answer = match handle {
+        Some(handle) => {
+            handle.abort();
+            "Decision is canceled."
+        }
+        None => {
+            let is_pending = repos
+                .offered_post_repo
+                .get_offered_post(message.chat_id(), message.id)
+                .await
+                .map(|post| post.status == OfferedPostStatus::Pending)
+                .unwrap_or(false);
+            if !is_pending {
+                cx.requester
+                    .answer_callback_query(cx.update.id.to_string())
+                    .text("Too late, the decision is already applied.")
+                    .send_retry()
+                    .await?;
+                return Ok(());
+            }
+            "The decision was interrupted and is not applied, please decide again."
+        }
+    };
+    repos
+        .offered_post_repo
+        .clear_scheduled_decision(message.chat_id(), message.id)
+        .await?;
+    let tally = if *VOTING_QUORUM > 0 {
+        Some(
+            repos
+                .vote_repo
+                .get_tally(message.chat_id(), message.id)
+                .await?,
+        )
+    } else {
+        None
+    };
+    cx.requester
+        .edit_message_reply_markup(message.chat_id(), message.id)
+        .reply_markup(build_keyboard(
+            origin.has_caption(),
+            origin.text().is_some(),
+            tally,
+        ))
+        .send_retry()
+        .await?;
+    cx.requester
+        .answer_callback_query(cx.update.id.to_string())
+        .text(answer)
+        .send_retry()
+        .await?;
+    Ok(())
+}
+
+async fn restore_keyboard(
+    bot: &Bot,
+    message: &Message,
+    origin: Option<&Message>,
+    tally: Option<(i64, i64)>,
+) {
+    let keyboard = message.reply_markup.clone().unwrap_or_else(|| {
+        build_keyboard(
+            origin.map(|origin| origin.has_caption()).unwrap_or(false),
+            origin.and_then(|origin| origin.text()).is_some(),
+            tally,
+        )
+    });
+    let _ = bot
+        .edit_message_reply_markup(message.chat_id(), message.id)
+        .reply_markup(keyboard)
+        .send_retry()
+        .await;
+}
+
+async fn apply_decision(
+    bot: &Bot,
+    data: &String,
+    message: &Message,
+    origin: &Message,
+    moderator: &User,
+    repos: &Repos,
+) -> Result<(), HandlerError> {
+    let offered_post = repos
+        .offered_post_repo
+        .get_offered_post(message.chat_id(), message.id)
+        .await;
+    if offered_post.is_ok()
+        && !repos
+            .offered_post_repo
+            .claim_decision(message.chat_id(), message.id)
+            .await?
+    {
+        return Err(HandlerError::validation(
+            "This suggestion is already decided or withdrawn.",
+        ));
+    }
+    let result =
+        apply_claimed_decision(bot, data, message, origin, moderator, offered_post, repos).await;
+    if result.is_err() {
+        let _ = repos
+            .offered_post_repo
+            .release_decision(message.chat_id(), message.id)
+            .await;
+    }
+    result
+}
+
+async fn apply_claimed_decision(
+    bot: &Bot,
+    data: &String,
+    message: &Message,
+    origin: &Message,
+    moderator: &User,
+    offered_post: Result<OfferedPost, sqlx::Error>,
+    repos: &Repos,
+) -> Result<(), HandlerError> {
+    let is_accept = data.starts_with(ACCEPT_CALLBACK);
+    let already_published = offered_post
+        .as_ref()
+        .ok()
+        .and_then(|post| post.channel_message_id)
+        .filter(|_| is_accept);
+    let published_message_id = if already_published.is_some() {
+        already_published
+    } else if is_accept {
+        let credit = match &offered_post {
+            Ok(post) => get_credit(
+                post.author.as_deref(),
+                repos
+                    .user_settings_repo
+                    .get_settings_or_default(post.chat_id)
+                    .await
+                    .credit,
+            ),
+            Err(_) => None,
+        };
+        let published_message_id = publish(bot, data, message, origin, moderator, credit).await?;
+        let _ = repos
+            .offered_post_repo
+            .update_channel_message_id(message.chat_id(), message.id, published_message_id)
             .await;
+        Some(published_message_id)
+    } else {
+        None
+    };
+    let published_link = published_message_id
+        .and_then(|message_id| channel_post_link(CHANNEL_ID.as_str(), message_id));
+    if let Some(message_id) =
+        published_message_id.filter(|_| already_published.is_none() && is_crosspost_enabled())
+    {
+        start_crosspost(
+            bot.clone(),
+            CrosspostPayload {
+                channel_id: CHANNEL_ID.to_string(),
+                message_id,
+                link: published_link.clone(),
+                author: offered_post
+                    .as_ref()
+                    .ok()
+                    .and_then(|post| post.author.clone()),
+                kind: Some(origin.kind_name().to_string()),
+                text: origin
+                    .text()
+                    .or_else(|| origin.caption())
+                    .map(|text| text.to_string()),
+            },
+            ADMINS_CHAT_ID.to_string(),
+            origin.id,
+        );
+    }
+    let mut event = json!({
+        "moderator": moderator.ftm_title(),
+        "link": published_link,
+        "silent": data.starts_with(SILENT_DECLINE_CALLBACK),
+    });
+    if let Some(post) = offered_post.as_ref().ok().filter(|post| !post.is_anonymous) {
+        event["chat_id"] = json!(post.chat_id);
+        event["message_id"] = json!(post.message_id);
+    }
+    if !data.starts_with(SILENT_DECLINE_CALLBACK) {
         match offered_post {
             Ok(post) => {
-                match get_pic(is_accept, cached_pic_repo, pic_repo).await {
+                match get_pic(is_accept, &repos.cached_pic_repo, &repos.pic_repo).await {
                     None => {
-                        let _ = cx
-                            .requester
-                            .send_message(
-                                ChatId::Id(post.chat_id),
-                                if is_accept {
-                                    "🎉 Post is published."
-                                } else {
-                                    "🚧 Post was rejected. Send me something cooler."
-                                },
-                            )
+                        let text = if is_accept {
+                            match &published_link {
+                                Some(link) => format!("🎉 Post is published.\n{}", link),
+                                None => "🎉 Post is published.".to_string(),
+                            }
+                        } else {
+                            "🚧 Post was rejected. Send me something cooler.".to_string()
+                        };
+                        let _ = bot
+                            .send_message(ChatId::Id(post.chat_id), text)
                             .reply_to_message_id(post.message_id)
-                            .send()
+                            .send_retry()
                             .await;
                     }
                     Some(pic) => match pic {
                         GetPicResult::Raw(filename, vector) => {
-                            if let Ok(response) = cx
-                                .requester
+                            let mut request = bot
                                 .send_animation(
                                     ChatId::Id(post.chat_id),
                                     InputFile::memory(filename.to_string(), vector),
                                 )
-                                .reply_to_message_id(post.message_id)
-                                .send()
-                                .await
-                            {
+                                .reply_to_message_id(post.message_id);
+                            if let Some(link) = &published_link {
+                                request = request.caption(link.to_string());
+                            }
+                            if let Ok(response) = request.send_retry().await {
                                 if let Some(video) = response.video() {
-                                    let _ = cached_pic_repo
+                                    let _ = repos
+                                        .cached_pic_repo
                                         .save_cached_pic(CachedPic {
                                             image_name: filename,
                                             image_file_id: video.file_id.to_string(),
@@ -321,19 +926,20 @@ async fn callback_handler(
                             }
                         }
                         GetPicResult::FileId(file_id) => {
-                            let _ = cx
-                                .requester
+                            let mut request = bot
                                 .send_video(ChatId::Id(post.chat_id), InputFile::file_id(file_id))
-                                .reply_to_message_id(post.message_id)
-                                .send()
-                                .await;
+                                .reply_to_message_id(post.message_id);
+                            if let Some(link) = &published_link {
+                                request = request.caption(link.to_string());
+                            }
+                            let _ = request.send_retry().await;
                         }
                     },
                 };
                 let save_result = if is_accept {
-                    stats_repo.increment_accepted(post.chat_id).await
+                    repos.stats_repo.increment_accepted(post.chat_id).await
                 } else {
-                    stats_repo.increment_declined(post.chat_id).await
+                    repos.stats_repo.increment_declined(post.chat_id).await
                 };
                 match save_result {
                     Err(e) => {
@@ -348,34 +954,461 @@ async fn callback_handler(
             Err(_) => {}
         }
     }
-    cx.requester
-        .delete_message(message.chat_id(), message.id)
-        .send()
+    let _ = repos
+        .offered_post_repo
+        .update_status(
+            message.chat_id(),
+            message.id,
+            if is_accept {
+                OfferedPostStatus::Accepted
+            } else {
+                OfferedPostStatus::Declined
+            },
+        )
+        .await;
+    inc_counter(
+        SUGGESTIONS_DECIDED,
+        &[
+            ("decision", if is_accept { "accepted" } else { "declined" }),
+            ("kind", origin.kind_name()),
+        ],
+    );
+    emit(
+        &repos.webhook_event_repo,
+        if is_accept {
+            WebhookEventKind::SuggestionAccepted
+        } else {
+            WebhookEventKind::SuggestionDeclined
+        },
+        event,
+    )
+    .await;
+    if *KEEP_HISTORY {
+        bot.edit_message_text(
+            message.chat_id(),
+            message.id,
+            decision_summary(message, data, moderator, published_link.as_deref()),
+        )
+        .send_retry()
         .await?;
+    } else {
+        bot.delete_message(message.chat_id(), message.id)
+            .send_retry()
+            .await?;
+    }
     Ok(())
 }
 
-async fn simple_copy(
-    cx: &UpdateWithCx<Bot, CallbackQuery>,
+fn decision_summary(
+    message: &Message,
+    data: &str,
+    moderator: &User,
+    published_link: Option<&str>,
+) -> String {
+    let decision = if data.starts_with(WITHOUT_TEXT_CALLBACK) {
+        "✅ Accepted without text"
+    } else if data.starts_with(ACCEPT_CALLBACK) {
+        "✅ Accepted"
+    } else if data.starts_with(SILENT_DECLINE_CALLBACK) {
+        "🗿 Silently declined"
+    } else {
+        "❌ Declined"
+    };
+    let mut summary = format!(
+        "{}\n{} by {} at {}",
+        message
+            .text()
+            .and_then(|text| text.lines().next())
+            .unwrap_or(""),
+        decision,
+        moderator.ftm_title(),
+        Local::now().format("%H:%M")
+    );
+    if let Some(link) = published_link {
+        summary.push_str(&format!(" → {}", link));
+    }
+    summary
+}
+
+async fn publish(
+    bot: &Bot,
     data: &String,
     message: &Message,
     origin: &Message,
-) -> Result<(), HandlerError> {
-    let _mes = cx
-        .requester
-        .copy_message(CHANNEL_ID.to_string(), message.chat_id(), origin.id)
-        .send()
+    moderator: &User,
+    credit: Option<Credit>,
+) -> Result<i32, HandlerError> {
+    let with_caption = origin.has_caption() && !data.starts_with(WITHOUT_TEXT_CALLBACK);
+    let (signature, markup) = match credit {
+        Some(Credit::Caption(signature)) => (Some(signature), None),
+        Some(Credit::Button(markup)) => (None, Some(markup)),
+        None => (None, None),
+    };
+    let caption = transform_caption(
+        origin.caption().filter(|_| with_caption),
+        origin
+            .caption_entities()
+            .filter(|_| with_caption)
+            .unwrap_or(&[]),
+        signature.as_deref(),
+        CAPTION_LIMIT,
+    );
+    let prepared = if let Some(doc) = origin.document() {
+        Some(prepare_upload(bot, doc).await)
+    } else if let Some(photos) = origin.photo().filter(|_| *MEDIA_PROCESSING) {
+        Some(prepare_photo_upload(bot, photos).await)
+    } else {
+        None
+    };
+    if let Some(prepared) = prepared {
+        match prepared {
+            Ok(Some(upload)) => {
+                return send_upload(bot, upload, caption.as_ref(), markup.as_ref()).await
+            }
+            Ok(None) => {}
+            Err(e) => {
+                warn!("Can not re-upload document, due to error {:?}", e);
+                let _ = bot
+                    .send_message(
+                        ADMINS_CHAT_ID.to_string(),
+                        format!(
+                            "⚠️ {}, media re-upload failed, the post is copied as is.\n{}",
+                            moderator.ftm_title(),
+                            e
+                        ),
+                    )
+                    .reply_to_message_id(message.id)
+                    .send_retry()
+                    .await;
+            }
+        }
+    }
+    simple_copy(
+        bot,
+        data,
+        message,
+        origin,
+        signature.as_deref(),
+        markup.as_ref(),
+    )
+    .await
+}
+
+struct Upload {
+    media_type: MediaType,
+    file: InputFile,
+    _temp_files: Vec<TempFile>,
+}
+
+async fn prepare_upload(bot: &Bot, doc: &Document) -> Result<Option<Upload>, HandlerError> {
+    let mime_format = format_from_mime(doc);
+    if mime_format.is_none() && !needs_sniffing(doc) {
+        return Ok(None);
+    }
+    if mime_format.map(|format| format.media_type()) == Some(MediaType::Unsupported) {
+        return Ok(None);
+    }
+    let mut file = download_doc_file(doc, bot, "bin").await?;
+    let format = match sniff_format(&file.header().await?).or(mime_format) {
+        Some(format) => format,
+        None => return Ok(None),
+    };
+    let media_type = format.media_type();
+    if media_type == MediaType::Unsupported {
+        return Ok(None);
+    }
+    if media_type == MediaType::Photo
+        && doc.file_size.unwrap_or(0) > PHOTO_UPLOAD_LIMIT
+        && !*MEDIA_PROCESSING
+    {
+        return Err(HandlerError::validation(
+            "Image is too big to be sent as a photo",
+        ));
+    }
+    file.set_extension(format.extension()).await?;
+    if media_type == MediaType::Photo && *MEDIA_PROCESSING {
+        let data = tokio::fs::read(&file.path)
+            .await
+            .map_err(|e| HandlerError::Io(format!("Can not read temp file: {:?}", e)))?;
+        return Ok(Some(Upload {
+            media_type,
+            file: InputFile::memory("image.jpg", process_in_background(data).await?),
+            _temp_files: vec![file],
+        }));
+    }
+    if media_type == MediaType::Animation && should_transcode_gif(doc.file_size.unwrap_or(0)) {
+        if let Some(converted) = gif_to_mp4(&file.path).await {
+            return Ok(Some(Upload {
+                media_type,
+                file: InputFile::file(&converted.path),
+                _temp_files: vec![file, converted],
+            }));
+        }
+    }
+    Ok(Some(Upload {
+        media_type,
+        file: InputFile::file(&file.path),
+        _temp_files: vec![file],
+    }))
+}
+
+async fn prepare_photo_upload(
+    bot: &Bot,
+    photos: &[PhotoSize],
+) -> Result<Option<Upload>, HandlerError> {
+    let photo = match photos.last() {
+        Some(photo) => photo,
+        None => return Ok(None),
+    };
+    let data = download_photo_vec(photo, bot)
+        .await
+        .ok_or(HandlerError::Io("Can not download photo".to_string()))?;
+    Ok(Some(Upload {
+        media_type: MediaType::Photo,
+        file: InputFile::memory("image.jpg", process_in_background(data).await?),
+        _temp_files: vec![],
+    }))
+}
+
+async fn process_in_background(data: Vec<u8>) -> Result<Vec<u8>, HandlerError> {
+    tokio::task::spawn_blocking(move || process_image(&data))
+        .await
+        .map_err(|e| HandlerError::External(format!("Image processing failed: {:?}", e)))?
+        .map_err(|e| HandlerError::Validation(format!("Can not process image: {:?}", e)))
+}
+
+async fn send_upload(
+    bot: &Bot,
+    upload: Upload,
+    caption: Option<&Caption>,
+    markup: Option<&InlineKeyboardMarkup>,
+) -> Result<i32, HandlerError> {
+    let caption = caption.map(|caption| entities_to_html(&caption.text, &caption.entities));
+    let sent = match upload.media_type {
+        MediaType::Photo => {
+            let mut r = bot.send_photo(CHANNEL_ID.to_string(), upload.file);
+            if let Some(caption) = caption {
+                r = r.caption(caption).parse_mode(ParseMode::Html);
+            }
+            if let Some(markup) = markup {
+                r = r.reply_markup(markup.clone());
+            }
+            r.send_retry_unsent().await?
+        }
+        MediaType::Animation => {
+            let mut r = bot.send_animation(CHANNEL_ID.to_string(), upload.file);
+            if let Some(caption) = caption {
+                r = r.caption(caption).parse_mode(ParseMode::Html);
+            }
+            if let Some(markup) = markup {
+                r = r.reply_markup(markup.clone());
+            }
+            r.send_retry_unsent().await?
+        }
+        _ => {
+            let mut r = bot.send_video(CHANNEL_ID.to_string(), upload.file);
+            if let Some(caption) = caption {
+                r = r.caption(caption).parse_mode(ParseMode::Html);
+            }
+            if let Some(markup) = markup {
+                r = r.reply_markup(markup.clone());
+            }
+            r.send_retry_unsent().await?
+        }
+    };
+    Ok(sent.id)
+}
+
+async fn offer_to_admins(
+    bot: &Bot,
+    message: &Message,
+    submitter: &str,
+    anonymous: bool,
+    is_edited: bool,
+    notes: &[String],
+) -> Result<(i32, Message), HandlerError> {
+    let forwarded = if anonymous {
+        bot.copy_message(ADMINS_CHAT_ID.to_string(), message.chat_id(), message.id)
+            .send_retry()
+            .await?
+            .message_id
+    } else {
+        bot.forward_message(ADMINS_CHAT_ID.to_string(), message.chat_id(), message.id)
+            .send_retry()
+            .await?
+            .id
+    };
+    let keyboard = bot
+        .send_message(
+            ADMINS_CHAT_ID.to_string(),
+            format!(
+                "From: {}{}\n{}We going to shitpost it?",
+                submitter,
+                if is_edited { " (edited)" } else { "" },
+                notes
+                    .iter()
+                    .map(|note| format!("{}\n", note))
+                    .collect::<String>()
+            ),
+        )
+        .reply_to_message_id(forwarded)
+        .reply_markup(build_keyboard(
+            message.has_caption(),
+            message.text().is_some(),
+            (*VOTING_QUORUM > 0).then(|| (0, 0)),
+        ))
+        .send_retry()
         .await?;
-    if data.starts_with(WITHOUT_TEXT_CALLBACK) && origin.has_caption() {
-        cx.requester
-            .edit_message_caption(CHANNEL_ID.to_string(), _mes.message_id)
-            .send()
+    Ok((forwarded, keyboard))
+}
+
+async fn retract_offer(
+    bot: &Bot,
+    admin_chat_id: i64,
+    admin_chat_message_id: i32,
+    admin_chat_additional_message_id: Option<i32>,
+) {
+    let _ = bot
+        .delete_message(admin_chat_id, admin_chat_message_id)
+        .send_retry()
+        .await;
+    if let Some(additional_message_id) = admin_chat_additional_message_id {
+        let _ = bot
+            .delete_message(admin_chat_id, additional_message_id)
+            .send_retry()
+            .await;
+    }
+}
+
+fn flag_notes(hits: &[FilterHit]) -> Vec<String> {
+    hits.iter()
+        .filter(|hit| hit.action == FilterAction::Flag)
+        .map(|hit| format!("⚠️ Flagged: {}", hit.reason))
+        .collect()
+}
+
+async fn simple_copy(
+    bot: &Bot,
+    data: &String,
+    message: &Message,
+    origin: &Message,
+    signature: Option<&str>,
+    markup: Option<&InlineKeyboardMarkup>,
+) -> Result<i32, HandlerError> {
+    let transformed = signature.is_some() || is_caption_transformed();
+    if let Some(text) = origin.text().filter(|_| transformed) {
+        let text = transform_caption(
+            Some(text),
+            origin.entities().unwrap_or(&[]),
+            signature,
+            TEXT_LIMIT,
+        )
+        .ok_or(HandlerError::validation("Empty post text"))?;
+        let mut r = bot
+            .send_message(CHANNEL_ID.to_string(), text.text)
+            .entities(text.entities);
+        if let Some(markup) = markup {
+            r = r.reply_markup(markup.clone());
+        }
+        return Ok(r.send_retry_unsent().await?.id);
+    }
+    let with_caption = origin.has_caption() && !data.starts_with(WITHOUT_TEXT_CALLBACK);
+    let caption = if origin.supports_caption() && transformed {
+        transform_caption(
+            origin.caption().filter(|_| with_caption),
+            origin
+                .caption_entities()
+                .filter(|_| with_caption)
+                .unwrap_or(&[]),
+            signature,
+            CAPTION_LIMIT,
+        )
+    } else {
+        None
+    };
+    let mut r = bot.copy_message(CHANNEL_ID.to_string(), message.chat_id(), origin.id);
+    if let Some(caption) = &caption {
+        r = r
+            .caption(caption.text.to_string())
+            .caption_entities(caption.entities.to_vec());
+    }
+    if let Some(markup) = markup {
+        r = r.reply_markup(markup.clone());
+    }
+    let _mes = r.send_retry_unsent().await?;
+    if caption.is_none() && data.starts_with(WITHOUT_TEXT_CALLBACK) && origin.has_caption() {
+        bot.edit_message_caption(CHANNEL_ID.to_string(), _mes.message_id)
+            .send_retry()
             .await?;
     }
-    Ok(())
+    Ok(_mes.message_id)
 }
 
-fn build_keyboard(has_caption: bool, only_text: bool) -> InlineKeyboardMarkup {
+async fn vote(
+    cx: &UpdateWithCx<Bot, CallbackQuery>,
+    data: String,
+    message: &Message,
+    origin: &Message,
+    vote_repo: &VoteRepo,
+) -> Result<Option<String>, HandlerError> {
+    let is_owner = VOTING_OWNERS.contains(&cx.update.from.id);
+    if data != VOTE_UP_CALLBACK && data != VOTE_DOWN_CALLBACK {
+        if is_owner {
+            return Ok(Some(data));
+        }
+        return Err(HandlerError::permission(
+            "Only owners can decide directly, please vote.",
+        ));
+    }
+    let is_accept = data == VOTE_UP_CALLBACK;
+    vote_repo
+        .save_vote(message.chat_id(), message.id, cx.update.from.id, is_accept)
+        .await?;
+    let (accepted, declined) = vote_repo.get_tally(message.chat_id(), message.id).await?;
+    if accepted >= *VOTING_QUORUM {
+        return Ok(Some(ACCEPT_CALLBACK.to_string()));
+    }
+    if declined >= *VOTING_QUORUM {
+        return Ok(Some(DECLINE_CALLBACK.to_string()));
+    }
+    cx.requester
+        .edit_message_reply_markup(message.chat_id(), message.id)
+        .reply_markup(build_keyboard(
+            origin.has_caption(),
+            origin.text().is_some(),
+            Some((accepted, declined)),
+        ))
+        .send_retry()
+        .await?;
+    cx.requester
+        .answer_callback_query(cx.update.id.to_string())
+        .text(format!(
+            "Vote counted: 👍 {} / 👎 {} of {} needed.",
+            accepted, declined, *VOTING_QUORUM
+        ))
+        .send_retry()
+        .await?;
+    Ok(None)
+}
+
+fn build_keyboard(
+    has_caption: bool,
+    only_text: bool,
+    tally: Option<(i64, i64)>,
+) -> InlineKeyboardMarkup {
+    let mut keyboard = InlineKeyboardMarkup::default();
+    if let Some((accepted, declined)) = tally {
+        keyboard = keyboard.append_row(vec![
+            InlineKeyboardButton::callback(
+                format!("👍 {}", accepted),
+                VOTE_UP_CALLBACK.to_string(),
+            ),
+            InlineKeyboardButton::callback(
+                format!("👎 {}", declined),
+                VOTE_DOWN_CALLBACK.to_string(),
+            ),
+        ]);
+    }
     let accept_button =
         InlineKeyboardButton::callback("✅ Accept".to_string(), ACCEPT_CALLBACK.to_string());
     let decline_button =
@@ -385,7 +1418,7 @@ fn build_keyboard(has_caption: bool, only_text: bool) -> InlineKeyboardMarkup {
         SILENT_DECLINE_CALLBACK.to_string(),
     );
     if only_text {
-        InlineKeyboardMarkup::default()
+        keyboard
             .append_row(vec![accept_button, decline_button])
             .append_row(vec![silent_decline_button])
     } else if has_caption {
@@ -393,10 +1426,10 @@ fn build_keyboard(has_caption: bool, only_text: bool) -> InlineKeyboardMarkup {
             "☢️ Without text".to_string(),
             WITHOUT_TEXT_CALLBACK.to_string(),
         );
-        InlineKeyboardMarkup::default()
+        keyboard
             .append_row(vec![accept_button, accept_without_text_button])
             .append_row(vec![decline_button])
     } else {
-        InlineKeyboardMarkup::default().append_row(vec![accept_button, decline_button])
+        keyboard.append_row(vec![accept_button, decline_button])
     }
 }
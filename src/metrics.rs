@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server, StatusCode};
+use lazy_static::lazy_static;
+use log::warn;
+
+use crate::data::repo::offered_post_repo::OfferedPostRepo;
+use crate::utils::result_utils::FatalValueMapper;
+use crate::ADMINS_CHAT_ID;
+
+static METRICS_ADDR_KEY: &str = "METRICS_ADDR";
+
+pub static SUGGESTIONS_RECEIVED: &str = "fm_suggestions_received_total";
+pub static SUGGESTIONS_DECIDED: &str = "fm_suggestions_decided_total";
+pub static CALLBACK_ERRORS: &str = "fm_callback_errors_total";
+pub static TELEGRAM_ERRORS: &str = "fm_telegram_errors_total";
+static DB_QUERY_DURATION: &str = "fm_db_query_duration_seconds";
+static PENDING_SUGGESTIONS: &str = "fm_pending_suggestions";
+
+const BUCKETS: [f64; 10] = [0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0];
+
+static DESCRIPTIONS: [(&str, &str, &str); 6] = [
+    (
+        SUGGESTIONS_RECEIVED,
+        "counter",
+        "Suggestions received by kind.",
+    ),
+    (
+        SUGGESTIONS_DECIDED,
+        "counter",
+        "Suggestions decided by decision and kind.",
+    ),
+    (CALLBACK_ERRORS, "counter", "Callback queries that failed."),
+    (TELEGRAM_ERRORS, "counter", "Telegram API errors by kind."),
+    (DB_QUERY_DURATION, "histogram", "Database query latency."),
+    (
+        PENDING_SUGGESTIONS,
+        "gauge",
+        "Suggestions waiting for a decision.",
+    ),
+];
+
+struct Histogram {
+    buckets: [u64; BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+lazy_static! {
+    static ref COUNTERS: Mutex<BTreeMap<(&'static str, String), u64>> = Mutex::new(BTreeMap::new());
+    static ref HISTOGRAMS: Mutex<BTreeMap<&'static str, Histogram>> = Mutex::new(BTreeMap::new());
+}
+
+pub fn inc_counter(name: &'static str, labels: &[(&str, &str)]) {
+    let labels = labels
+        .iter()
+        .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+        .collect::<Vec<String>>()
+        .join(",");
+    *COUNTERS.lock().unwrap().entry((name, labels)).or_insert(0) += 1;
+}
+
+pub struct QueryTimer {
+    query: &'static str,
+    started_at: Instant,
+}
+
+impl Drop for QueryTimer {
+    fn drop(&mut self) {
+        let elapsed = self.started_at.elapsed().as_secs_f64();
+        let mut histograms = HISTOGRAMS.lock().unwrap();
+        let histogram = histograms.entry(self.query).or_insert(Histogram {
+            buckets: [0; BUCKETS.len()],
+            sum: 0.0,
+            count: 0,
+        });
+        BUCKETS
+            .iter()
+            .zip(histogram.buckets.iter_mut())
+            .filter(|(bound, _)| elapsed <= **bound)
+            .for_each(|(_, bucket)| *bucket += 1);
+        histogram.sum += elapsed;
+        histogram.count += 1;
+    }
+}
+
+pub fn query_timer(query: &'static str) -> QueryTimer {
+    QueryTimer {
+        query,
+        started_at: Instant::now(),
+    }
+}
+
+pub fn start_metrics_server(offered_post_repo: OfferedPostRepo) {
+    let addr = match env::var(METRICS_ADDR_KEY) {
+        Ok(addr) if !addr.is_empty() => addr
+            .parse::<SocketAddr>()
+            .map_value_or_exit(format!("Invalid {} value {}", METRICS_ADDR_KEY, addr)),
+        _ => return,
+    };
+    let make_service = make_service_fn(move |_| {
+        let offered_post_repo = offered_post_repo.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let offered_post_repo = offered_post_repo.clone();
+                async move { Ok::<_, Infallible>(handle(request, &offered_post_repo).await) }
+            }))
+        }
+    });
+    tokio::spawn(async move {
+        log::info!("Metrics are served on {}", addr);
+        if let Err(e) = Server::bind(&addr).serve(make_service).await {
+            warn!("Metrics server stopped, due to error {:?}", e);
+        }
+    });
+}
+
+async fn handle(request: Request<Body>, offered_post_repo: &OfferedPostRepo) -> Response<Body> {
+    if request.method() != Method::GET || request.uri().path() != "/metrics" {
+        return Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+            .unwrap();
+    }
+    let pending = match ADMINS_CHAT_ID.parse::<i64>() {
+        Ok(admin_chat_id) => offered_post_repo.count_pending(admin_chat_id).await.ok(),
+        Err(_) => None,
+    };
+    Response::builder()
+        .header(header::CONTENT_TYPE, "text/plain; version=0.0.4")
+        .body(Body::from(render(pending)))
+        .unwrap()
+}
+
+fn render(pending: Option<i64>) -> String {
+    let counters = COUNTERS.lock().unwrap();
+    let histograms = HISTOGRAMS.lock().unwrap();
+    let mut result = String::new();
+    for (name, kind, help) in DESCRIPTIONS.iter() {
+        result.push_str(&format!(
+            "# HELP {} {}\n# TYPE {} {}\n",
+            name, help, name, kind
+        ));
+        if *name == DB_QUERY_DURATION {
+            for (query, histogram) in histograms.iter() {
+                for (bound, count) in BUCKETS.iter().zip(histogram.buckets.iter()) {
+                    result.push_str(&format!(
+                        "{}_bucket{{query=\"{}\",le=\"{}\"}} {}\n",
+                        name, query, bound, count
+                    ));
+                }
+                result.push_str(&format!(
+                    "{}_bucket{{query=\"{}\",le=\"+Inf\"}} {}\n",
+                    name, query, histogram.count
+                ));
+                result.push_str(&format!(
+                    "{}_sum{{query=\"{}\"}} {}\n",
+                    name, query, histogram.sum
+                ));
+                result.push_str(&format!(
+                    "{}_count{{query=\"{}\"}} {}\n",
+                    name, query, histogram.count
+                ));
+            }
+        } else if *name == PENDING_SUGGESTIONS {
+            if let Some(pending) = pending {
+                result.push_str(&format!("{} {}\n", name, pending));
+            }
+        } else {
+            for ((_, labels), value) in counters.iter().filter(|((key, _), _)| key == name) {
+                if labels.is_empty() {
+                    result.push_str(&format!("{} {}\n", name, value));
+                } else {
+                    result.push_str(&format!("{}{{{}}} {}\n", name, labels, value));
+                }
+            }
+        }
+    }
+    result
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
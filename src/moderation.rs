@@ -0,0 +1,227 @@
+use std::env;
+use std::time::Duration;
+
+use futures_util::future::BoxFuture;
+use lazy_static::lazy_static;
+use log::warn;
+use reqwest::multipart::{Form, Part};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use teloxide::types::Message;
+use teloxide::Bot;
+
+use crate::utils::document_utils::{download_animate_vec, download_doc_vec, download_photo_vec};
+use crate::utils::message_utils::ExtMessage;
+
+static MODERATION_ENDPOINT_KEY: &str = "MODERATION_ENDPOINT";
+static MODERATION_TIMEOUT_KEY: &str = "MODERATION_TIMEOUT";
+static MODERATION_MAX_MEDIA_SIZE_KEY: &str = "MODERATION_MAX_MEDIA_SIZE";
+
+lazy_static! {
+    static ref MODERATION_TIMEOUT: u64 = env::var(MODERATION_TIMEOUT_KEY)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(10);
+    static ref MODERATION_MAX_MEDIA_SIZE: u32 = env::var(MODERATION_MAX_MEDIA_SIZE_KEY)
+        .ok()
+        .and_then(|value| value.parse::<u32>().ok())
+        .unwrap_or(5 * 1024)
+        * 1024;
+    pub static ref MODERATION_HOOK: Box<dyn ModerationHook> =
+        match env::var(MODERATION_ENDPOINT_KEY) {
+            Ok(endpoint) if !endpoint.is_empty() => Box::new(HttpModerationHook::new(endpoint)),
+            _ => Box::new(NoopModerationHook),
+        };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Allow,
+    Flag,
+    Reject,
+}
+
+impl Verdict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Allow => "allow",
+            Verdict::Flag => "flag",
+            Verdict::Reject => "reject",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModerationResult {
+    pub verdict: Verdict,
+    #[serde(default)]
+    pub score: f64,
+    pub reason: Option<String>,
+}
+
+impl ModerationResult {
+    pub fn note(&self) -> String {
+        format!(
+            "🤖 Classifier: {} ({:.2}){}",
+            self.verdict.as_str(),
+            self.score,
+            self.reason
+                .as_ref()
+                .map(|reason| format!(" - {}", reason))
+                .unwrap_or_default()
+        )
+    }
+}
+
+#[derive(Serialize)]
+pub struct SubmissionMeta {
+    pub chat_id: i64,
+    pub message_id: i32,
+    pub kind: String,
+    pub text: Option<String>,
+    pub file_name: Option<String>,
+    pub mime_type: Option<String>,
+}
+
+impl SubmissionMeta {
+    pub fn from_message(message: &Message) -> Self {
+        let document = message.document();
+        SubmissionMeta {
+            chat_id: message.chat_id(),
+            message_id: message.id,
+            kind: message.kind_name().to_string(),
+            text: message
+                .text()
+                .or_else(|| message.caption())
+                .map(|text| text.to_string()),
+            file_name: document.and_then(|doc| doc.file_name.clone()),
+            mime_type: document
+                .and_then(|doc| doc.mime_type.as_ref())
+                .map(|mime| mime.to_string()),
+        }
+    }
+}
+
+pub trait ModerationHook: Send + Sync {
+    fn check<'a>(
+        &'a self,
+        meta: &'a SubmissionMeta,
+        media: Option<&'a [u8]>,
+    ) -> BoxFuture<'a, Option<ModerationResult>>;
+
+    fn needs_media(&self) -> bool {
+        false
+    }
+}
+
+pub struct NoopModerationHook;
+
+impl ModerationHook for NoopModerationHook {
+    fn check<'a>(
+        &'a self,
+        _meta: &'a SubmissionMeta,
+        _media: Option<&'a [u8]>,
+    ) -> BoxFuture<'a, Option<ModerationResult>> {
+        Box::pin(async { None })
+    }
+}
+
+pub struct HttpModerationHook {
+    endpoint: String,
+    client: Client,
+}
+
+impl HttpModerationHook {
+    pub fn new(endpoint: String) -> Self {
+        HttpModerationHook {
+            endpoint,
+            client: Client::builder()
+                .timeout(Duration::from_secs(*MODERATION_TIMEOUT))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    async fn request(
+        &self,
+        meta: &SubmissionMeta,
+        media: Option<&[u8]>,
+    ) -> Result<ModerationResult, reqwest::Error> {
+        let mut form = Form::new().text("meta", serde_json::to_string(meta).unwrap_or_default());
+        if let Some(media) = media {
+            form = form.part(
+                "media",
+                Part::bytes(media.to_vec())
+                    .file_name(meta.file_name.clone().unwrap_or(meta.kind.clone())),
+            );
+        }
+        self.client
+            .post(&self.endpoint)
+            .multipart(form)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ModerationResult>()
+            .await
+    }
+}
+
+impl ModerationHook for HttpModerationHook {
+    fn check<'a>(
+        &'a self,
+        meta: &'a SubmissionMeta,
+        media: Option<&'a [u8]>,
+    ) -> BoxFuture<'a, Option<ModerationResult>> {
+        Box::pin(async move {
+            match self.request(meta, media).await {
+                Ok(result) => Some(result),
+                Err(e) => {
+                    warn!(
+                        "Moderation endpoint failed for message {} - id {}, due to error {:?}",
+                        meta.message_id, meta.chat_id, e
+                    );
+                    None
+                }
+            }
+        })
+    }
+
+    fn needs_media(&self) -> bool {
+        true
+    }
+}
+
+pub async fn moderate(bot: &Bot, message: &Message) -> Option<ModerationResult> {
+    let meta = SubmissionMeta::from_message(message);
+    let media = if MODERATION_HOOK.needs_media() {
+        download_media(bot, message).await
+    } else {
+        None
+    };
+    MODERATION_HOOK.check(&meta, media.as_deref()).await
+}
+
+async fn download_media(bot: &Bot, message: &Message) -> Option<Vec<u8>> {
+    let fits = |size: Option<u32>| {
+        size.filter(|size| *size <= *MODERATION_MAX_MEDIA_SIZE)
+            .is_some()
+    };
+    if let Some(animation) = message.animation() {
+        if !fits(animation.file_size) {
+            return None;
+        }
+        download_animate_vec(animation, bot).await
+    } else if let Some(document) = message.document() {
+        if !fits(document.file_size) {
+            return None;
+        }
+        download_doc_vec(document, bot).await
+    } else {
+        let photo = message.photo()?.last()?;
+        if !fits(photo.file_size) {
+            return None;
+        }
+        download_photo_vec(photo, bot).await
+    }
+}
@@ -0,0 +1,73 @@
+use teloxide::prelude::*;
+
+use crate::data::model::relay_message::RelayMessage;
+use crate::data::repo::relay_message_repo::RelayMessageRepo;
+use crate::utils::error_utils::HandlerError;
+use crate::utils::request_utils::SendRetry;
+
+pub async fn relay_to_admins(
+    cx: &UpdateWithCx<Bot, Message>,
+    relay_message_repo: &RelayMessageRepo,
+    admins_chat_id: &str,
+) -> Result<bool, HandlerError> {
+    let reply_to = match cx.update.reply_to_message() {
+        Some(reply_to) => reply_to,
+        None => return Ok(false),
+    };
+    let relay = match relay_message_repo
+        .get_by_user_message(cx.update.chat_id(), reply_to.id)
+        .await
+    {
+        Ok(relay) => relay,
+        Err(_) => return Ok(false),
+    };
+    let copied = cx
+        .requester
+        .copy_message(admins_chat_id.to_string(), cx.update.chat_id(), cx.update.id)
+        .reply_to_message_id(relay.admin_message_id)
+        .allow_sending_without_reply(true)
+        .send_retry()
+        .await?;
+    relay_message_repo
+        .save_relay_message(RelayMessage::new(
+            relay.admin_chat_id,
+            copied.message_id,
+            cx.update.chat_id(),
+            cx.update.id,
+        ))
+        .await?;
+    Ok(true)
+}
+
+pub async fn relay_to_user(
+    cx: &UpdateWithCx<Bot, Message>,
+    relay_message_repo: &RelayMessageRepo,
+) -> Result<bool, HandlerError> {
+    let reply_to = match cx.update.reply_to_message() {
+        Some(reply_to) => reply_to,
+        None => return Ok(false),
+    };
+    let relay = match relay_message_repo
+        .get_by_admin_message(cx.update.chat_id(), reply_to.id)
+        .await
+    {
+        Ok(relay) => relay,
+        Err(_) => return Ok(false),
+    };
+    let copied = cx
+        .requester
+        .copy_message(relay.user_chat_id, cx.update.chat_id(), cx.update.id)
+        .reply_to_message_id(relay.user_message_id)
+        .allow_sending_without_reply(true)
+        .send_retry()
+        .await?;
+    relay_message_repo
+        .save_relay_message(RelayMessage::new(
+            cx.update.chat_id(),
+            cx.update.id,
+            relay.user_chat_id,
+            copied.message_id,
+        ))
+        .await?;
+    Ok(true)
+}
@@ -0,0 +1,205 @@
+use std::env;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use lazy_static::lazy_static;
+use serde_json::json;
+use teloxide::prelude::*;
+use teloxide::types::ChatId;
+use tokio::time::interval;
+
+use crate::data::model::offered_post::{OfferedPost, OfferedPostStatus};
+use crate::data::repo::offered_post_repo::OfferedPostRepo;
+use crate::data::repo::stats_repo::StatsRepo;
+use crate::data::repo::webhook_event_repo::WebhookEventRepo;
+use crate::metrics::{inc_counter, SUGGESTIONS_DECIDED};
+use crate::utils::error_utils::HandlerError;
+use crate::utils::request_utils::SendRetry;
+use crate::utils::time_utils::format_age;
+use crate::webhooks::{emit, WebhookEventKind};
+use crate::{cancel_pending_decision, ADMINS_CHAT_ID, KEEP_HISTORY};
+
+static REMIND_AFTER_HOURS_KEY: &str = "REMIND_AFTER_HOURS";
+static REMIND_INTERVAL_HOURS_KEY: &str = "REMIND_INTERVAL_HOURS";
+static AUTO_DECLINE_AFTER_DAYS_KEY: &str = "AUTO_DECLINE_AFTER_DAYS";
+static AUTO_DECLINE_MESSAGE_KEY: &str = "AUTO_DECLINE_MESSAGE";
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const DIGEST_LIMIT: usize = 20;
+
+lazy_static! {
+    static ref REMIND_AFTER_HOURS: i64 = env::var(REMIND_AFTER_HOURS_KEY)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0);
+    static ref REMIND_INTERVAL_HOURS: u64 = env::var(REMIND_INTERVAL_HOURS_KEY)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(24);
+    static ref AUTO_DECLINE_AFTER_DAYS: i64 = env::var(AUTO_DECLINE_AFTER_DAYS_KEY)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0);
+    static ref AUTO_DECLINE_MESSAGE: Option<String> = env::var(AUTO_DECLINE_MESSAGE_KEY)
+        .ok()
+        .filter(|value| !value.is_empty());
+}
+
+pub fn start_scheduler(
+    bot: Bot,
+    offered_post_repo: OfferedPostRepo,
+    stats_repo: StatsRepo,
+    webhook_event_repo: WebhookEventRepo,
+) {
+    if *REMIND_AFTER_HOURS <= 0 && *AUTO_DECLINE_AFTER_DAYS <= 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut ticker = interval(CHECK_INTERVAL);
+        let mut last_digest: Option<Instant> = None;
+        loop {
+            ticker.tick().await;
+            if *AUTO_DECLINE_AFTER_DAYS > 0 {
+                if let Err(err) =
+                    auto_decline(&bot, &offered_post_repo, &stats_repo, &webhook_event_repo).await
+                {
+                    log::warn!("{}", err);
+                }
+            }
+            let digest_due = last_digest
+                .map(|at| at.elapsed() >= Duration::from_secs(*REMIND_INTERVAL_HOURS * 60 * 60))
+                .unwrap_or(true);
+            if *REMIND_AFTER_HOURS > 0 && digest_due {
+                match remind(&bot, &offered_post_repo).await {
+                    Ok(_) => last_digest = Some(Instant::now()),
+                    Err(err) => log::warn!("{}", err),
+                }
+            }
+        }
+    });
+}
+
+async fn remind(bot: &Bot, offered_post_repo: &OfferedPostRepo) -> Result<(), HandlerError> {
+    let now = Utc::now().timestamp();
+    let posts = offered_post_repo
+        .get_pending_older_than(now - *REMIND_AFTER_HOURS * 60 * 60)
+        .await?;
+    if posts.is_empty() {
+        return Ok(());
+    }
+    let _list = posts
+        .iter()
+        .take(DIGEST_LIMIT)
+        .map(|post| {
+            format!(
+                "  - {} | {} | {}",
+                if post.is_anonymous {
+                    "🕶 anonymous"
+                } else {
+                    post.author.as_deref().unwrap_or("unknown")
+                },
+                post.kind.as_deref().unwrap_or("unknown"),
+                format_age(now, post.created_at)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    bot.send_message(
+        ADMINS_CHAT_ID.to_string(),
+        format!(
+            "⏰ {} suggestion(s) wait longer than {}h:\n{}\nSee /pending",
+            posts.len(),
+            *REMIND_AFTER_HOURS,
+            _list
+        ),
+    )
+    .send_retry()
+    .await?;
+    Ok(())
+}
+
+async fn auto_decline(
+    bot: &Bot,
+    offered_post_repo: &OfferedPostRepo,
+    stats_repo: &StatsRepo,
+    webhook_event_repo: &WebhookEventRepo,
+) -> Result<(), HandlerError> {
+    let posts = offered_post_repo
+        .get_pending_older_than(Utc::now().timestamp() - *AUTO_DECLINE_AFTER_DAYS * 24 * 60 * 60)
+        .await?;
+    for post in posts {
+        if let Err(err) = expire(
+            bot,
+            &post,
+            offered_post_repo,
+            stats_repo,
+            webhook_event_repo,
+        )
+        .await
+        {
+            log::warn!("{}", err);
+        }
+    }
+    Ok(())
+}
+
+async fn expire(
+    bot: &Bot,
+    post: &OfferedPost,
+    offered_post_repo: &OfferedPostRepo,
+    stats_repo: &StatsRepo,
+    webhook_event_repo: &WebhookEventRepo,
+) -> Result<(), HandlerError> {
+    cancel_pending_decision(post.admin_chat_id, post.admin_chat_message_id);
+    offered_post_repo
+        .update_status(
+            post.admin_chat_id,
+            post.admin_chat_message_id,
+            OfferedPostStatus::Declined,
+        )
+        .await?;
+    stats_repo.increment_declined(post.chat_id).await?;
+    inc_counter(
+        SUGGESTIONS_DECIDED,
+        &[
+            ("decision", "expired"),
+            ("kind", post.kind.as_deref().unwrap_or("unknown")),
+        ],
+    );
+    emit(
+        webhook_event_repo,
+        WebhookEventKind::SuggestionDeclined,
+        json!({
+            "chat_id": post.chat_id,
+            "message_id": post.message_id,
+            "expired": true,
+        }),
+    )
+    .await;
+    if *KEEP_HISTORY {
+        let _ = bot
+            .edit_message_text(
+                post.admin_chat_id,
+                post.admin_chat_message_id,
+                format!(
+                    "⌛ Declined automatically after {} day(s) without decision",
+                    *AUTO_DECLINE_AFTER_DAYS
+                ),
+            )
+            .send_retry()
+            .await;
+    } else {
+        let _ = bot
+            .delete_message(post.admin_chat_id, post.admin_chat_message_id)
+            .send_retry()
+            .await;
+    }
+    if let Some(text) = AUTO_DECLINE_MESSAGE.as_ref() {
+        bot.send_message(ChatId::Id(post.chat_id), text.to_string())
+            .reply_to_message_id(post.message_id)
+            .allow_sending_without_reply(true)
+            .send_retry()
+            .await?;
+    }
+    Ok(())
+}
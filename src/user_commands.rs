@@ -0,0 +1,242 @@
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+
+use crate::cancel_pending_decision;
+use crate::data::model::offered_post::OfferedPostStatus;
+use crate::data::model::stats::UserStats;
+use crate::data::repo::offered_post_repo::OfferedPostRepo;
+use crate::data::repo::stats_repo::StatsRepo;
+use crate::data::repo::user_settings_repo::UserSettingsRepo;
+use crate::utils::anon_utils::{AnonymousMode, ANONYMOUS_MODE};
+use crate::utils::credit_utils::{CreditPolicy, CREDIT_POLICY};
+use crate::utils::error_utils::HandlerError;
+use crate::utils::request_utils::SendRetry;
+
+pub static WITHDRAW_CALLBACK: &str = "withdraw";
+
+static STATS_CMD: &str = "/stats";
+static MY_CMD: &str = "/my";
+static CREDIT_CMD: &str = "/credit";
+static ANON_CMD: &str = "/anon";
+
+pub async fn exec_user_command(
+    text: &str,
+    cx: &UpdateWithCx<Bot, Message>,
+    stats_repo: &StatsRepo,
+    offered_post_repo: &OfferedPostRepo,
+    user_settings_repo: &UserSettingsRepo,
+) -> Result<(), HandlerError> {
+    if text.starts_with(STATS_CMD) {
+        stats(cx, stats_repo).await?
+    } else if text.starts_with(MY_CMD) {
+        my(cx, offered_post_repo).await?
+    } else if text.starts_with(CREDIT_CMD) {
+        credit(cx, user_settings_repo, text).await?
+    } else if text.starts_with(ANON_CMD) {
+        anon(cx, user_settings_repo, text).await?
+    }
+    Ok(())
+}
+
+pub async fn withdraw(
+    cx: &UpdateWithCx<Bot, CallbackQuery>,
+    data: &str,
+    offered_post_repo: &OfferedPostRepo,
+) -> Result<(), HandlerError> {
+    let message = cx
+        .update
+        .message
+        .as_ref()
+        .ok_or(HandlerError::missing("Message not found"))?;
+    let message_id = data
+        .trim_start_matches(WITHDRAW_CALLBACK)
+        .trim_start_matches(':')
+        .parse::<i32>()
+        .map_err(|_| HandlerError::Validation(format!("Invalid withdraw data: {}", data)))?;
+    let post = offered_post_repo
+        .get_user_offered_post(message.chat_id(), message_id)
+        .await?;
+    if post.status != OfferedPostStatus::Pending {
+        cx.requester
+            .answer_callback_query(cx.update.id.to_string())
+            .text("This suggestion is already moderated.")
+            .send_retry()
+            .await?;
+        return Ok(());
+    }
+    cancel_pending_decision(post.admin_chat_id, post.admin_chat_message_id);
+    offered_post_repo
+        .update_status(
+            post.admin_chat_id,
+            post.admin_chat_message_id,
+            OfferedPostStatus::Withdrawn,
+        )
+        .await?;
+    let _ = cx
+        .requester
+        .delete_message(post.admin_chat_id, post.admin_chat_message_id)
+        .send_retry()
+        .await;
+    if let Some(additional_message_id) = post.admin_chat_additional_message_id {
+        let _ = cx
+            .requester
+            .delete_message(post.admin_chat_id, additional_message_id)
+            .send_retry()
+            .await;
+    }
+    cx.requester
+        .edit_message_text(message.chat_id(), message.id, "↩️ Suggestion withdrawn.")
+        .send_retry()
+        .await?;
+    cx.requester
+        .answer_callback_query(cx.update.id.to_string())
+        .send_retry()
+        .await?;
+    Ok(())
+}
+
+async fn stats(cx: &UpdateWithCx<Bot, Message>, stats_repo: &StatsRepo) -> Result<(), HandlerError> {
+    let UserStats {
+        offered_count,
+        accepted_count,
+        declined_count,
+        ..
+    } = stats_repo
+        .get_stat_for_user_or_default(cx.update.chat_id())
+        .await;
+    cx.reply_to(format!(
+        "Your stats are:\nOffered: {}\nAccepted: {}\nDeclined: {}",
+        offered_count, accepted_count, declined_count
+    ))
+    .send_retry()
+    .await?;
+    Ok(())
+}
+
+async fn my(
+    cx: &UpdateWithCx<Bot, Message>,
+    offered_post_repo: &OfferedPostRepo,
+) -> Result<(), HandlerError> {
+    let posts = offered_post_repo
+        .get_pending_offered_posts(cx.update.chat_id())
+        .await?;
+    if posts.is_empty() {
+        cx.reply_to("You have no pending suggestions.").send_retry().await?;
+        return Ok(());
+    }
+    cx.reply_to(format!("You have {} pending suggestion(s):", posts.len()))
+        .send_retry()
+        .await?;
+    for post in posts {
+        let withdraw_button = InlineKeyboardButton::callback(
+            "↩️ Withdraw".to_string(),
+            format!("{}:{}", WITHDRAW_CALLBACK, post.message_id),
+        );
+        cx.requester
+            .send_message(cx.update.chat_id(), "⏳ Waiting for moderation.")
+            .reply_to_message_id(post.message_id)
+            .allow_sending_without_reply(true)
+            .reply_markup(InlineKeyboardMarkup::default().append_row(vec![withdraw_button]))
+            .send_retry()
+            .await?;
+    }
+    Ok(())
+}
+
+async fn credit(
+    cx: &UpdateWithCx<Bot, Message>,
+    user_settings_repo: &UserSettingsRepo,
+    text: &str,
+) -> Result<(), HandlerError> {
+    match *CREDIT_POLICY {
+        CreditPolicy::Off => {
+            cx.reply_to("Posts in this channel are published anonymously.")
+                .send_retry()
+                .await?;
+            return Ok(());
+        }
+        CreditPolicy::Always => {
+            cx.reply_to("Posts in this channel are always signed with the author name.")
+                .send_retry()
+                .await?;
+            return Ok(());
+        }
+        CreditPolicy::Optional => {}
+    }
+    let credit = match text.trim_start_matches(CREDIT_CMD).trim() {
+        "on" => true,
+        "off" => false,
+        _ => {
+            let settings = user_settings_repo
+                .get_settings_or_default(cx.update.chat_id())
+                .await;
+            cx.reply_to(format!(
+                "Author credit is {}. Use /credit on or /credit off to change it.",
+                if settings.credit { "on" } else { "off" }
+            ))
+            .send_retry()
+            .await?;
+            return Ok(());
+        }
+    };
+    user_settings_repo
+        .set_credit(cx.update.chat_id(), credit)
+        .await?;
+    cx.reply_to(if credit {
+        "👤 Your accepted posts will be signed with your name."
+    } else {
+        "🕶 Your accepted posts will be published anonymously."
+    })
+    .send_retry()
+    .await?;
+    Ok(())
+}
+
+async fn anon(
+    cx: &UpdateWithCx<Bot, Message>,
+    user_settings_repo: &UserSettingsRepo,
+    text: &str,
+) -> Result<(), HandlerError> {
+    match *ANONYMOUS_MODE {
+        AnonymousMode::Off => {
+            cx.reply_to("Anonymous suggestions are not available in this channel.")
+                .send_retry()
+                .await?;
+            return Ok(());
+        }
+        AnonymousMode::Always => {
+            cx.reply_to("All suggestions are already anonymous for moderators.")
+                .send_retry()
+                .await?;
+            return Ok(());
+        }
+        AnonymousMode::Optional => {}
+    }
+    let anonymous = match text.trim_start_matches(ANON_CMD).trim() {
+        "on" => true,
+        "off" => false,
+        _ => {
+            let settings = user_settings_repo
+                .get_settings_or_default(cx.update.chat_id())
+                .await;
+            cx.reply_to(format!(
+                "Anonymous mode is {}. Use /anon on or /anon off to change it.",
+                if settings.anonymous { "on" } else { "off" }
+            ))
+            .send_retry()
+            .await?;
+            return Ok(());
+        }
+    };
+    user_settings_repo
+        .set_anonymous(cx.update.chat_id(), anonymous)
+        .await?;
+    cx.reply_to(if anonymous {
+        "🕶 Moderators will not see who sent your next suggestions."
+    } else {
+        "👤 Your next suggestions will be forwarded with your profile."
+    })
+    .send_retry()
+    .await?;
+    Ok(())
+}
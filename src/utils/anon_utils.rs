@@ -0,0 +1,36 @@
+use std::env;
+
+use lazy_static::lazy_static;
+use rand::Rng;
+
+static ANONYMOUS_MODE_KEY: &str = "ANONYMOUS_MODE";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AnonymousMode {
+    Off,
+    Optional,
+    Always,
+}
+
+lazy_static! {
+    pub static ref ANONYMOUS_MODE: AnonymousMode = match env::var(ANONYMOUS_MODE_KEY)
+        .unwrap_or(String::new())
+        .as_str()
+    {
+        "optional" => AnonymousMode::Optional,
+        "always" => AnonymousMode::Always,
+        _ => AnonymousMode::Off,
+    };
+}
+
+pub fn is_anonymous(user_wants_anonymity: bool) -> bool {
+    match *ANONYMOUS_MODE {
+        AnonymousMode::Off => false,
+        AnonymousMode::Optional => user_wants_anonymity,
+        AnonymousMode::Always => true,
+    }
+}
+
+pub fn generate_anon_token() -> String {
+    format!("anon#{:06x}", rand::thread_rng().gen_range(0..0x1000000))
+}
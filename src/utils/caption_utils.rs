@@ -0,0 +1,218 @@
+use std::env;
+
+use lazy_static::lazy_static;
+use teloxide::types::{MessageEntity, MessageEntityKind};
+
+static CAPTION_SIGNATURE_KEY: &str = "CAPTION_SIGNATURE";
+static CAPTION_HASHTAGS_KEY: &str = "CAPTION_HASHTAGS";
+static CAPTION_STRIP_LINKS_KEY: &str = "CAPTION_STRIP_LINKS";
+static CAPTION_STRIP_MENTIONS_KEY: &str = "CAPTION_STRIP_MENTIONS";
+
+pub static CAPTION_LIMIT: usize = 1024;
+pub static TEXT_LIMIT: usize = 4096;
+
+lazy_static! {
+    static ref CAPTION_SIGNATURE: Option<String> = env::var(CAPTION_SIGNATURE_KEY)
+        .ok()
+        .filter(|value| !value.is_empty());
+    static ref CAPTION_HASHTAGS: Option<String> = env::var(CAPTION_HASHTAGS_KEY)
+        .ok()
+        .filter(|value| !value.is_empty());
+    static ref CAPTION_STRIP_LINKS: bool = env::var(CAPTION_STRIP_LINKS_KEY)
+        .map(|value| value == "true")
+        .unwrap_or(false);
+    static ref CAPTION_STRIP_MENTIONS: bool = env::var(CAPTION_STRIP_MENTIONS_KEY)
+        .map(|value| value == "true")
+        .unwrap_or(false);
+}
+
+pub struct Caption {
+    pub text: String,
+    pub entities: Vec<MessageEntity>,
+}
+
+pub fn is_caption_transformed() -> bool {
+    CAPTION_SIGNATURE.is_some()
+        || CAPTION_HASHTAGS.is_some()
+        || *CAPTION_STRIP_LINKS
+        || *CAPTION_STRIP_MENTIONS
+}
+
+pub fn transform_caption(
+    text: Option<&str>,
+    entities: &[MessageEntity],
+    signature: Option<&str>,
+    limit: usize,
+) -> Option<Caption> {
+    let mut units = text.unwrap_or("").encode_utf16().collect::<Vec<u16>>();
+    let mut entities = entities.to_vec();
+    entities.sort_by_key(|entity| std::cmp::Reverse(entity.offset));
+    for entity in entities.clone() {
+        match &entity.kind {
+            MessageEntityKind::Url if *CAPTION_STRIP_LINKS => {
+                remove_range(&mut units, &mut entities, entity.offset, entity.length)
+            }
+            MessageEntityKind::Mention if *CAPTION_STRIP_MENTIONS => {
+                remove_range(&mut units, &mut entities, entity.offset, entity.length)
+            }
+            _ => {}
+        }
+    }
+    entities.retain(|entity| match entity.kind {
+        MessageEntityKind::TextLink { .. } => !*CAPTION_STRIP_LINKS,
+        MessageEntityKind::TextMention { .. } => !*CAPTION_STRIP_MENTIONS,
+        _ => entity.length > 0,
+    });
+    entities.sort_by_key(|entity| entity.offset);
+
+    let body = String::from_utf16_lossy(&units);
+    let body = body.trim_end();
+    let suffix = [
+        signature,
+        CAPTION_SIGNATURE.as_deref(),
+        CAPTION_HASHTAGS.as_deref(),
+    ]
+    .iter()
+    .flatten()
+    .map(|part| part.to_string())
+    .collect::<Vec<String>>()
+    .join("\n");
+    let suffix_len = suffix.encode_utf16().count();
+    let separator = if body.is_empty() || suffix.is_empty() {
+        ""
+    } else {
+        "\n\n"
+    };
+
+    let mut units = body.encode_utf16().collect::<Vec<u16>>();
+    let available = limit.saturating_sub(suffix_len + separator.len());
+    if units.len() > available {
+        let mut cut = available.saturating_sub(1);
+        if cut > 0 && (0xDC00..0xE000).contains(&units[cut]) {
+            cut -= 1;
+        }
+        units.truncate(cut);
+        units.extend("…".encode_utf16());
+        entities.retain(|entity| entity.offset < cut);
+        entities.iter_mut().for_each(|entity| {
+            entity.length = entity.length.min(cut - entity.offset);
+        });
+    } else {
+        entities.retain(|entity| entity.offset < units.len());
+        let len = units.len();
+        entities.iter_mut().for_each(|entity| {
+            entity.length = entity.length.min(len - entity.offset);
+        });
+    }
+
+    let text = format!(
+        "{}{}{}",
+        String::from_utf16_lossy(&units),
+        separator,
+        suffix
+    );
+    if text.is_empty() {
+        None
+    } else {
+        Some(Caption { text, entities })
+    }
+}
+
+fn remove_range(
+    units: &mut Vec<u16>,
+    entities: &mut Vec<MessageEntity>,
+    offset: usize,
+    length: usize,
+) {
+    let end = (offset + length).min(units.len());
+    if offset >= end {
+        return;
+    }
+    units.drain(offset..end);
+    let removed = end - offset;
+    entities.iter_mut().for_each(|entity| {
+        let entity_end = entity.offset + entity.length;
+        if entity.offset >= end {
+            entity.offset -= removed;
+        } else if entity_end > offset {
+            let overlap = entity_end.min(end) - entity.offset.max(offset);
+            entity.offset = entity.offset.min(offset);
+            entity.length -= overlap;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(kind: MessageEntityKind, offset: usize, length: usize) -> MessageEntity {
+        MessageEntity::new(kind, offset, length)
+    }
+
+    fn spans(entities: &[MessageEntity]) -> Vec<(usize, usize)> {
+        entities
+            .iter()
+            .map(|entity| (entity.offset, entity.length))
+            .collect()
+    }
+
+    #[test]
+    fn removal_shrinks_overlapping_entities() {
+        let mut units = "bold http://x.y tail".encode_utf16().collect::<Vec<u16>>();
+        let mut entities = vec![
+            entity(MessageEntityKind::Bold, 0, 10),
+            entity(MessageEntityKind::Italic, 7, 10),
+            entity(MessageEntityKind::Underline, 0, 20),
+            entity(MessageEntityKind::Code, 16, 4),
+        ];
+        remove_range(&mut units, &mut entities, 5, 10);
+        assert_eq!(String::from_utf16(&units).unwrap(), "bold  tail");
+        assert_eq!(spans(&entities), vec![(0, 5), (5, 2), (0, 10), (6, 4)]);
+    }
+
+    #[test]
+    fn truncation_does_not_split_surrogate_pair() {
+        let caption = transform_caption(Some("aaaaaaaa😀bbbb"), &[], None, 10).unwrap();
+        assert_eq!(caption.text, "aaaaaaaa…");
+    }
+
+    #[test]
+    fn truncation_keeps_pair_before_cut() {
+        let caption = transform_caption(Some("aaaaaaa😀bbbb"), &[], None, 10).unwrap();
+        assert_eq!(caption.text, "aaaaaaa😀…");
+    }
+
+    #[test]
+    fn signature_alone_forces_truncation() {
+        let text = "hello world";
+        let signature = "via @someone";
+        assert!(text.len() <= 20);
+        let caption = transform_caption(
+            Some(text),
+            &[
+                entity(MessageEntityKind::Bold, 0, 11),
+                entity(MessageEntityKind::Italic, 6, 5),
+            ],
+            Some(signature),
+            20,
+        )
+        .unwrap();
+        assert_eq!(caption.text, "hello…\n\nvia @someone");
+        assert_eq!(caption.text.encode_utf16().count(), 20);
+        assert_eq!(spans(&caption.entities), vec![(0, 5)]);
+    }
+
+    #[test]
+    fn fitting_caption_is_kept() {
+        let caption = transform_caption(
+            Some("short"),
+            &[entity(MessageEntityKind::Bold, 0, 5)],
+            Some("via @someone"),
+            CAPTION_LIMIT,
+        )
+        .unwrap();
+        assert_eq!(caption.text, "short\n\nvia @someone");
+        assert_eq!(spans(&caption.entities), vec![(0, 5)]);
+    }
+}
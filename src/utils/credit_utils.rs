@@ -0,0 +1,65 @@
+use std::env;
+
+use lazy_static::lazy_static;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+
+static CREDIT_POLICY_KEY: &str = "CREDIT_POLICY";
+static CREDIT_STYLE_KEY: &str = "CREDIT_STYLE";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CreditPolicy {
+    Off,
+    Optional,
+    Always,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CreditStyle {
+    Caption,
+    Button,
+}
+
+pub enum Credit {
+    Caption(String),
+    Button(InlineKeyboardMarkup),
+}
+
+lazy_static! {
+    pub static ref CREDIT_POLICY: CreditPolicy = match env::var(CREDIT_POLICY_KEY)
+        .unwrap_or(String::new())
+        .as_str()
+    {
+        "optional" => CreditPolicy::Optional,
+        "always" => CreditPolicy::Always,
+        _ => CreditPolicy::Off,
+    };
+    static ref CREDIT_STYLE: CreditStyle = match env::var(CREDIT_STYLE_KEY)
+        .unwrap_or(String::new())
+        .as_str()
+    {
+        "button" => CreditStyle::Button,
+        _ => CreditStyle::Caption,
+    };
+}
+
+pub fn get_credit(author: Option<&str>, user_wants_credit: bool) -> Option<Credit> {
+    let enabled = match *CREDIT_POLICY {
+        CreditPolicy::Off => false,
+        CreditPolicy::Optional => user_wants_credit,
+        CreditPolicy::Always => true,
+    };
+    let author = author.filter(|author| enabled && !author.is_empty())?;
+    let signature = format!("via {}", author);
+    match *CREDIT_STYLE {
+        CreditStyle::Button => match author.strip_prefix('@') {
+            Some(username) => Some(Credit::Button(
+                InlineKeyboardMarkup::default().append_row(vec![InlineKeyboardButton::url(
+                    signature,
+                    format!("https://t.me/{}", username),
+                )]),
+            )),
+            None => Some(Credit::Caption(signature)),
+        },
+        CreditStyle::Caption => Some(Credit::Caption(signature)),
+    }
+}
@@ -1,9 +1,53 @@
+use std::env;
+use std::path::PathBuf;
+
 use bytes::BufMut;
 use futures_util::TryStreamExt;
+use rand::Rng;
 use teloxide::net::Download;
 use teloxide::prelude::{Request, Requester};
-use teloxide::types::{Animation, Document};
+use teloxide::types::{Animation, Document, PhotoSize};
 use teloxide::Bot;
+use tokio::io::AsyncReadExt;
+
+use crate::utils::error_utils::HandlerError;
+
+pub static DOWNLOAD_LIMIT: u32 = 20 * 1024 * 1024;
+pub static PHOTO_UPLOAD_LIMIT: u32 = 10 * 1024 * 1024;
+
+pub struct TempFile {
+    pub path: PathBuf,
+}
+
+impl TempFile {
+    pub async fn header(&self) -> Result<Vec<u8>, HandlerError> {
+        let mut header = vec![0; 64];
+        let mut file = tokio::fs::File::open(&self.path)
+            .await
+            .map_err(|e| HandlerError::Io(format!("Can not open temp file: {:?}", e)))?;
+        let read = file
+            .read(&mut header)
+            .await
+            .map_err(|e| HandlerError::Io(format!("Can not read temp file: {:?}", e)))?;
+        header.truncate(read);
+        Ok(header)
+    }
+
+    pub async fn set_extension(&mut self, extension: &str) -> Result<(), HandlerError> {
+        let path = self.path.with_extension(extension);
+        tokio::fs::rename(&self.path, &path)
+            .await
+            .map_err(|e| HandlerError::Io(format!("Can not rename temp file: {:?}", e)))?;
+        self.path = path;
+        Ok(())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
 
 pub async fn download_doc_vec(doc: &Document, bot: &Bot) -> Option<Vec<u8>> {
     _download(doc.file_id.to_string(), bot).await
@@ -13,6 +57,36 @@ pub async fn download_animate_vec(doc: &Animation, bot: &Bot) -> Option<Vec<u8>>
     _download(doc.file_id.to_string(), bot).await
 }
 
+pub async fn download_photo_vec(photo: &PhotoSize, bot: &Bot) -> Option<Vec<u8>> {
+    _download(photo.file_id.to_string(), bot).await
+}
+
+pub async fn download_doc_file(
+    doc: &Document,
+    bot: &Bot,
+    extension: &str,
+) -> Result<TempFile, HandlerError> {
+    if doc.file_size.unwrap_or(0) > DOWNLOAD_LIMIT {
+        return Err(HandlerError::validation("File is too big to download"));
+    }
+    let file = bot.get_file(doc.file_id.to_string()).send().await?;
+    let temp = TempFile {
+        path: env::temp_dir().join(format!(
+            "{}-{}.{}",
+            doc.file_unique_id,
+            rand::thread_rng().gen::<u32>(),
+            extension
+        )),
+    };
+    let mut destination = tokio::fs::File::create(&temp.path)
+        .await
+        .map_err(|e| HandlerError::Io(format!("Can not create temp file: {:?}", e)))?;
+    bot.download_file(&file.file_path, &mut destination)
+        .await
+        .map_err(|e| HandlerError::Io(format!("Can not download file: {:?}", e)))?;
+    Ok(temp)
+}
+
 async fn _download(file_id: String, bot: &Bot) -> Option<Vec<u8>> {
     let file = bot.get_file(file_id).send().await.ok()?;
     let stream = bot.download_file_stream(&file.file_path);
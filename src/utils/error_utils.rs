@@ -1,44 +1,91 @@
 use teloxide::RequestError;
 
-#[derive(Debug, PartialEq)]
-pub struct HandlerError {
-    pub message: String,
+use crate::metrics::{inc_counter, TELEGRAM_ERRORS};
+
+#[derive(Debug)]
+pub enum HandlerError {
+    Telegram(RequestError),
+    Db(sqlx::Error),
+    Missing(String),
+    Permission(String),
+    Validation(String),
+    Io(String),
+    External(String),
 }
 
 impl HandlerError {
-    pub fn new(data: String) -> Self {
-        HandlerError {
-            message: data.into(),
-        }
+    pub fn missing(data: &str) -> Self {
+        HandlerError::Missing(data.to_string())
+    }
+
+    pub fn permission(data: &str) -> Self {
+        HandlerError::Permission(data.to_string())
     }
 
-    pub fn from_str(data: &str) -> Self {
-        HandlerError {
-            message: String::from(data).into(),
+    pub fn validation(data: &str) -> Self {
+        HandlerError::Validation(data.to_string())
+    }
+
+    pub fn user_message(&self) -> String {
+        match self {
+            HandlerError::Telegram(RequestError::RetryAfter(seconds)) => {
+                format!("⏳ Telegram asks to wait {}s, try again later.", seconds)
+            }
+            HandlerError::Telegram(_) => "⚠️ Telegram request failed, try again.".to_string(),
+            HandlerError::Db(_) => "⚠️ Database error, try again.".to_string(),
+            HandlerError::Missing(message)
+            | HandlerError::Permission(message)
+            | HandlerError::Validation(message) => format!("⚠️ {}", message),
+            HandlerError::Io(_) | HandlerError::External(_) => {
+                "⚠️ Something went wrong, try again.".to_string()
+            }
         }
     }
 }
 
 impl From<RequestError> for HandlerError {
     fn from(e: RequestError) -> Self {
-        HandlerError::new(format!("Teloxide request error: {:?}", e).to_string())
+        let kind = match &e {
+            RequestError::ApiError { kind, .. } => {
+                let kind = format!("{:?}", kind);
+                kind.split(|c| c == '(' || c == ' ')
+                    .next()
+                    .unwrap_or("")
+                    .to_string()
+            }
+            RequestError::MigrateToChatId(_) => "MigrateToChatId".to_string(),
+            RequestError::RetryAfter(_) => "RetryAfter".to_string(),
+            RequestError::NetworkError(_) => "NetworkError".to_string(),
+            RequestError::InvalidJson(_) => "InvalidJson".to_string(),
+            RequestError::Io(_) => "Io".to_string(),
+        };
+        inc_counter(TELEGRAM_ERRORS, &[("kind", &kind)]);
+        HandlerError::Telegram(e)
     }
 }
 
 impl From<sqlx::Error> for HandlerError {
     fn from(e: sqlx::Error) -> Self {
-        HandlerError::new(format!("Sqlx error: {:?}", e).to_string())
+        HandlerError::Db(e)
     }
 }
 
 impl<T> From<Option<T>> for HandlerError {
     fn from(_: Option<T>) -> Self {
-        HandlerError::new(String::from("Option is None"))
+        HandlerError::missing("Option is None")
     }
 }
 
 impl std::fmt::Display for HandlerError {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(fmt, "{}", self.message)
+        match self {
+            HandlerError::Telegram(e) => write!(fmt, "Teloxide request error: {:?}", e),
+            HandlerError::Db(e) => write!(fmt, "Sqlx error: {:?}", e),
+            HandlerError::Missing(message) => write!(fmt, "Missing data: {}", message),
+            HandlerError::Permission(message) => write!(fmt, "Permission denied: {}", message),
+            HandlerError::Validation(message) => write!(fmt, "Invalid data: {}", message),
+            HandlerError::Io(message) => write!(fmt, "IO error: {}", message),
+            HandlerError::External(message) => write!(fmt, "External service error: {}", message),
+        }
     }
 }
@@ -0,0 +1,161 @@
+use teloxide::types::{MessageEntity, MessageEntityKind};
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn entities_to_html(text: &str, entities: &[MessageEntity]) -> String {
+    let units = text.encode_utf16().collect::<Vec<u16>>();
+    let mut entities = entities
+        .iter()
+        .filter(|entity| entity.length > 0 && tags(entity).is_some())
+        .collect::<Vec<&MessageEntity>>();
+    entities.sort_by(|a, b| a.offset.cmp(&b.offset).then(b.length.cmp(&a.length)));
+    let mut entities = entities.into_iter().peekable();
+
+    let mut html = String::new();
+    let mut open: Vec<(usize, String)> = Vec::new();
+    let mut chunk_start = 0;
+    for position in 0..=units.len() {
+        let closes = open.iter().any(|(end, _)| *end <= position);
+        let opens = entities
+            .peek()
+            .map(|entity| entity.offset <= position)
+            .unwrap_or(false);
+        if !closes && !opens {
+            continue;
+        }
+        html.push_str(&escape_html(&String::from_utf16_lossy(
+            &units[chunk_start..position],
+        )));
+        chunk_start = position;
+        while open
+            .last()
+            .map(|(end, _)| *end <= position)
+            .unwrap_or(false)
+        {
+            html.push_str(&open.pop().unwrap().1);
+        }
+        while let Some(entity) = entities.next_if(|entity| entity.offset <= position) {
+            if position == units.len() {
+                continue;
+            }
+            let (open_tag, close_tag) = tags(entity).unwrap();
+            let parent_end = open.last().map(|(end, _)| *end).unwrap_or(units.len());
+            let end = (position + entity.length).min(parent_end);
+            html.push_str(&open_tag);
+            open.push((end, close_tag));
+        }
+    }
+    html
+}
+
+fn tags(entity: &MessageEntity) -> Option<(String, String)> {
+    let (open, close) = match &entity.kind {
+        MessageEntityKind::Bold => ("<b>".to_string(), "</b>"),
+        MessageEntityKind::Italic => ("<i>".to_string(), "</i>"),
+        MessageEntityKind::Underline => ("<u>".to_string(), "</u>"),
+        MessageEntityKind::Strikethrough => ("<s>".to_string(), "</s>"),
+        MessageEntityKind::Code => ("<code>".to_string(), "</code>"),
+        MessageEntityKind::Pre {
+            language: Some(language),
+        } => (
+            format!("<pre><code class=\"language-{}\">", escape_html(language)),
+            "</code></pre>",
+        ),
+        MessageEntityKind::Pre { language: None } => ("<pre>".to_string(), "</pre>"),
+        MessageEntityKind::TextLink { url } => (
+            format!("<a href=\"{}\">", escape_html(url.as_str())),
+            "</a>",
+        ),
+        MessageEntityKind::TextMention { user } => {
+            (format!("<a href=\"tg://user?id={}\">", user.id), "</a>")
+        }
+        _ => return None,
+    };
+    Some((open, close.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(kind: MessageEntityKind, offset: usize, length: usize) -> MessageEntity {
+        MessageEntity::new(kind, offset, length)
+    }
+
+    #[test]
+    fn nested_bold_and_italic() {
+        let html = entities_to_html(
+            "bold italic",
+            &[
+                entity(MessageEntityKind::Italic, 5, 6),
+                entity(MessageEntityKind::Bold, 0, 11),
+            ],
+        );
+        assert_eq!(html, "<b>bold <i>italic</i></b>");
+    }
+
+    #[test]
+    fn text_link_url_is_escaped() {
+        let html = entities_to_html(
+            "link",
+            &[entity(
+                MessageEntityKind::TextLink {
+                    url: "https://example.com/?b=1&c=\"2\"".parse().unwrap(),
+                },
+                0,
+                4,
+            )],
+        );
+        let href = html
+            .strip_prefix("<a href=\"")
+            .and_then(|html| html.strip_suffix("\">link</a>"))
+            .unwrap();
+        assert!(href.starts_with("https://example.com/?b=1&amp;c="));
+        assert!(!href.contains('"'));
+        assert!(!href
+            .replace("&amp;", "")
+            .replace("&quot;", "")
+            .contains('&'));
+    }
+
+    #[test]
+    fn pre_with_language() {
+        let html = entities_to_html(
+            "a < b",
+            &[entity(
+                MessageEntityKind::Pre {
+                    language: Some("rust".to_string()),
+                },
+                0,
+                5,
+            )],
+        );
+        assert_eq!(
+            html,
+            "<pre><code class=\"language-rust\">a &lt; b</code></pre>"
+        );
+    }
+
+    #[test]
+    fn astral_plane_offsets() {
+        let html = entities_to_html("😀 bold 😀", &[entity(MessageEntityKind::Bold, 3, 4)]);
+        assert_eq!(html, "😀 <b>bold</b> 😀");
+    }
+
+    #[test]
+    fn zero_length_entity_is_ignored() {
+        let html = entities_to_html("a & b", &[entity(MessageEntityKind::Bold, 2, 0)]);
+        assert_eq!(html, "a &amp; b");
+    }
+
+    #[test]
+    fn entity_at_the_end_of_text() {
+        let html = entities_to_html("hello world", &[entity(MessageEntityKind::Italic, 6, 5)]);
+        assert_eq!(html, "hello <i>world</i>");
+    }
+}
@@ -0,0 +1,279 @@
+use std::env;
+use std::fs;
+use std::io::Cursor;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::{self, FilterType};
+use image::{ColorType, DynamicImage, GenericImageView, ImageError, Rgba, RgbaImage};
+use lazy_static::lazy_static;
+use rusttype::{point, Font, Scale};
+
+use crate::utils::result_utils::FatalValueMapper;
+
+static MEDIA_PROCESSING_KEY: &str = "MEDIA_PROCESSING";
+static WATERMARK_IMAGE_KEY: &str = "WATERMARK_IMAGE";
+static WATERMARK_TEXT_KEY: &str = "WATERMARK_TEXT";
+static WATERMARK_FONT_KEY: &str = "WATERMARK_FONT";
+static WATERMARK_POSITION_KEY: &str = "WATERMARK_POSITION";
+static WATERMARK_OPACITY_KEY: &str = "WATERMARK_OPACITY";
+
+static MAX_PHOTO_SIDE: u32 = 2560;
+static JPEG_QUALITY: u8 = 90;
+static WATERMARK_SCALE: f32 = 0.05;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum WatermarkPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+enum Watermark {
+    Image(RgbaImage),
+    Text(Font<'static>, String),
+}
+
+lazy_static! {
+    pub static ref MEDIA_PROCESSING: bool = env::var(MEDIA_PROCESSING_KEY)
+        .map(|value| value == "true")
+        .unwrap_or(false);
+    static ref WATERMARK: Option<Watermark> = load_watermark();
+    static ref WATERMARK_POSITION: WatermarkPosition = match env::var(WATERMARK_POSITION_KEY)
+        .unwrap_or(String::new())
+        .as_str()
+    {
+        "top-left" => WatermarkPosition::TopLeft,
+        "top-right" => WatermarkPosition::TopRight,
+        "bottom-left" => WatermarkPosition::BottomLeft,
+        "center" => WatermarkPosition::Center,
+        _ => WatermarkPosition::BottomRight,
+    };
+    static ref WATERMARK_OPACITY: f32 = env::var(WATERMARK_OPACITY_KEY)
+        .ok()
+        .and_then(|value| value.parse::<f32>().ok())
+        .map(|value| value.max(0.0).min(1.0))
+        .unwrap_or(0.5);
+}
+
+pub fn init_media_processing() {
+    if *MEDIA_PROCESSING {
+        lazy_static::initialize(&WATERMARK);
+    }
+}
+
+fn load_watermark() -> Option<Watermark> {
+    if let Ok(path) = env::var(WATERMARK_IMAGE_KEY) {
+        let watermark =
+            image::open(&path).map_value_or_exit(format!("Can not open watermark image {}", path));
+        return Some(Watermark::Image(watermark.to_rgba8()));
+    }
+    let text = env::var(WATERMARK_TEXT_KEY)
+        .ok()
+        .filter(|text| !text.is_empty())?;
+    let path = env::var(WATERMARK_FONT_KEY).map_value_or_exit(format!(
+        "{} is required for a text watermark",
+        WATERMARK_FONT_KEY
+    ));
+    let font = fs::read(&path)
+        .ok()
+        .and_then(Font::try_from_vec)
+        .ok_or(())
+        .map_value_or_exit(format!("Can not load watermark font {}", path));
+    Some(Watermark::Text(font, text))
+}
+
+pub fn process_image(data: &[u8]) -> Result<Vec<u8>, ImageError> {
+    process(
+        data,
+        WATERMARK.as_ref(),
+        *WATERMARK_POSITION,
+        *WATERMARK_OPACITY,
+    )
+}
+
+fn process(
+    data: &[u8],
+    watermark: Option<&Watermark>,
+    position: WatermarkPosition,
+    opacity: f32,
+) -> Result<Vec<u8>, ImageError> {
+    let mut image = image::load_from_memory(data)?;
+    let (width, height) = image.dimensions();
+    if width > MAX_PHOTO_SIDE || height > MAX_PHOTO_SIDE {
+        image = image.resize(MAX_PHOTO_SIDE, MAX_PHOTO_SIDE, FilterType::Lanczos3);
+    }
+    let mut canvas = flatten(&image);
+    if let Some(watermark) = watermark {
+        let watermark = match watermark {
+            Watermark::Image(watermark) => scale_watermark(watermark, canvas.width()),
+            Watermark::Text(font, text) => render_text(font, text, canvas.height()),
+        };
+        apply_watermark(&mut canvas, &watermark, position, opacity);
+    }
+    let rgb = DynamicImage::ImageRgba8(canvas).to_rgb8();
+    let mut result = Cursor::new(Vec::new());
+    JpegEncoder::new_with_quality(&mut result, JPEG_QUALITY).encode(
+        rgb.as_raw(),
+        rgb.width(),
+        rgb.height(),
+        ColorType::Rgb8,
+    )?;
+    Ok(result.into_inner())
+}
+
+fn flatten(image: &DynamicImage) -> RgbaImage {
+    let mut canvas = image.to_rgba8();
+    canvas.pixels_mut().for_each(|pixel| {
+        let alpha = pixel[3] as f32 / 255.0;
+        for channel in 0..3 {
+            pixel[channel] = (pixel[channel] as f32 * alpha + 255.0 * (1.0 - alpha)) as u8;
+        }
+        pixel[3] = 255;
+    });
+    canvas
+}
+
+fn scale_watermark(watermark: &RgbaImage, width: u32) -> RgbaImage {
+    let max_width = (width / 4).max(1);
+    if watermark.width() <= max_width {
+        return watermark.clone();
+    }
+    let height = (watermark.height() as u64 * max_width as u64 / watermark.width() as u64).max(1);
+    imageops::resize(watermark, max_width, height as u32, FilterType::Lanczos3)
+}
+
+fn render_text(font: &Font, text: &str, height: u32) -> RgbaImage {
+    let scale = Scale::uniform((height as f32 * WATERMARK_SCALE).max(12.0));
+    let metrics = font.v_metrics(scale);
+    let glyphs = font
+        .layout(text, scale, point(0.0, metrics.ascent))
+        .collect::<Vec<_>>();
+    let width = glyphs
+        .iter()
+        .filter_map(|glyph| glyph.pixel_bounding_box())
+        .map(|bounds| bounds.max.x)
+        .max()
+        .unwrap_or(0)
+        .max(1) as u32;
+    let height = (metrics.ascent - metrics.descent).ceil().max(1.0) as u32;
+    let mut canvas = RgbaImage::new(width, height);
+    glyphs.iter().for_each(|glyph| {
+        if let Some(bounds) = glyph.pixel_bounding_box() {
+            glyph.draw(|x, y, coverage| {
+                let x = x as i32 + bounds.min.x;
+                let y = y as i32 + bounds.min.y;
+                if x >= 0 && y >= 0 && (x as u32) < width && (y as u32) < height {
+                    canvas.put_pixel(
+                        x as u32,
+                        y as u32,
+                        Rgba([255, 255, 255, (coverage * 255.0) as u8]),
+                    );
+                }
+            });
+        }
+    });
+    canvas
+}
+
+fn apply_watermark(
+    canvas: &mut RgbaImage,
+    watermark: &RgbaImage,
+    position: WatermarkPosition,
+    opacity: f32,
+) {
+    let margin = canvas.width().min(canvas.height()) / 50;
+    let right = canvas.width().saturating_sub(watermark.width() + margin);
+    let bottom = canvas.height().saturating_sub(watermark.height() + margin);
+    let (x, y) = match position {
+        WatermarkPosition::TopLeft => (margin, margin),
+        WatermarkPosition::TopRight => (right, margin),
+        WatermarkPosition::BottomLeft => (margin, bottom),
+        WatermarkPosition::BottomRight => (right, bottom),
+        WatermarkPosition::Center => (
+            canvas.width().saturating_sub(watermark.width()) / 2,
+            canvas.height().saturating_sub(watermark.height()) / 2,
+        ),
+    };
+    let mut watermark = watermark.clone();
+    watermark.pixels_mut().for_each(|pixel| {
+        pixel[3] = (pixel[3] as f32 * opacity) as u8;
+    });
+    imageops::overlay(canvas, &watermark, x, y);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/images");
+
+    fn fixture(name: &str) -> Vec<u8> {
+        fs::read(format!("{}/{}", FIXTURES, name)).unwrap()
+    }
+
+    #[test]
+    fn resizes_above_max_side() {
+        let result = process(
+            &fixture("large.png"),
+            None,
+            WatermarkPosition::BottomRight,
+            1.0,
+        )
+        .unwrap();
+        let image = image::load_from_memory(&result).unwrap();
+        assert_eq!(image.dimensions(), (MAX_PHOTO_SIDE, MAX_PHOTO_SIDE / 2));
+    }
+
+    #[test]
+    fn keeps_small_images_size() {
+        let result = process(
+            &fixture("watermark.png"),
+            None,
+            WatermarkPosition::BottomRight,
+            1.0,
+        )
+        .unwrap();
+        let image = image::load_from_memory(&result).unwrap();
+        assert_eq!(image.dimensions(), (10, 10));
+    }
+
+    #[test]
+    fn flattens_alpha_on_white() {
+        let image = image::load_from_memory(&fixture("transparent.png")).unwrap();
+        let canvas = flatten(&image);
+        assert_eq!(*canvas.get_pixel(0, 0), Rgba([255, 255, 255, 255]));
+        assert_eq!(*canvas.get_pixel(15, 15), Rgba([0, 0, 0, 255]));
+        let result = process(
+            &fixture("transparent.png"),
+            None,
+            WatermarkPosition::BottomRight,
+            1.0,
+        )
+        .unwrap();
+        let image = image::load_from_memory(&result).unwrap().to_rgb8();
+        assert!(image.get_pixel(1, 8).0.iter().all(|channel| *channel > 240));
+        assert!(image.get_pixel(14, 8).0.iter().all(|channel| *channel < 15));
+    }
+
+    #[test]
+    fn places_watermark() {
+        let watermark = image::load_from_memory(&fixture("watermark.png"))
+            .unwrap()
+            .to_rgba8();
+        let positions = [
+            (WatermarkPosition::TopLeft, "watermark_top_left.png"),
+            (WatermarkPosition::TopRight, "watermark_top_right.png"),
+            (WatermarkPosition::BottomLeft, "watermark_bottom_left.png"),
+            (WatermarkPosition::BottomRight, "watermark_bottom_right.png"),
+            (WatermarkPosition::Center, "watermark_center.png"),
+        ];
+        for (position, name) in positions.iter() {
+            let mut canvas = RgbaImage::from_pixel(100, 100, Rgba([0, 0, 255, 255]));
+            apply_watermark(&mut canvas, &watermark, *position, 1.0);
+            let golden = image::load_from_memory(&fixture(name)).unwrap().to_rgba8();
+            assert!(canvas == golden, "{:?} does not match {}", position, name);
+        }
+    }
+}
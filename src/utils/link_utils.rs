@@ -0,0 +1,9 @@
+pub fn channel_post_link(channel_id: &str, message_id: i32) -> Option<String> {
+    if let Some(username) = channel_id.strip_prefix('@') {
+        return Some(format!("https://t.me/{}/{}", username, message_id));
+    }
+    channel_id
+        .strip_prefix("-100")
+        .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+        .map(|id| format!("https://t.me/c/{}/{}", id, message_id))
+}
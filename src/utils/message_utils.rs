@@ -2,10 +2,47 @@ use teloxide::types::Message;
 
 pub(crate) trait ExtMessage {
     fn has_caption(&self) -> bool;
+    fn supports_caption(&self) -> bool;
+    fn kind_name(&self) -> &'static str;
 }
 
 impl ExtMessage for Message {
     fn has_caption(&self) -> bool {
         self.caption().unwrap_or("").len() > 0 || self.caption_entities().unwrap_or(&[]).len() > 0
     }
+
+    fn supports_caption(&self) -> bool {
+        self.photo().is_some()
+            || self.video().is_some()
+            || self.animation().is_some()
+            || self.document().is_some()
+            || self.audio().is_some()
+            || self.voice().is_some()
+    }
+
+    fn kind_name(&self) -> &'static str {
+        if self.text().is_some() {
+            "text"
+        } else if self.photo().is_some() {
+            "photo"
+        } else if self.animation().is_some() {
+            "animation"
+        } else if self.video().is_some() {
+            "video"
+        } else if self.document().is_some() {
+            "document"
+        } else if self.sticker().is_some() {
+            "sticker"
+        } else if self.audio().is_some() {
+            "audio"
+        } else if self.voice().is_some() {
+            "voice"
+        } else if self.video_note().is_some() {
+            "video note"
+        } else if self.poll().is_some() {
+            "poll"
+        } else {
+            "other"
+        }
+    }
 }
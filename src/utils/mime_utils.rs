@@ -1,24 +1,175 @@
 use teloxide::types::Document;
 
-pub fn is_image(doc: &Document) -> bool {
-    doc.mime_type
-        .as_ref()
-        .and_then(|mime| {
-            Some(vec!["image/jpeg", "image/png", "image/webp"].contains(&&*mime.to_string()))
-        })
-        .unwrap_or(false)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MediaFormat {
+    Jpeg,
+    Png,
+    Webp,
+    Gif,
+    Mp4,
+    Mov,
+    Webm,
+    Mkv,
+    Heic,
+    Avif,
 }
 
-pub fn is_animate(doc: &Document) -> bool {
-    doc.mime_type
-        .as_ref()
-        .and_then(|mime| Some(mime.to_string() == "image/gif"))
-        .unwrap_or(false)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MediaType {
+    Photo,
+    Animation,
+    Video,
+    Unsupported,
 }
 
-pub fn is_video(doc: &Document) -> bool {
-    doc.mime_type
-        .as_ref()
-        .and_then(|mime| Some(vec!["video/quicktime", "video/mp4"].contains(&&*mime.to_string())))
-        .unwrap_or(false)
+impl MediaFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            MediaFormat::Jpeg => "jpg",
+            MediaFormat::Png => "png",
+            MediaFormat::Webp => "webp",
+            MediaFormat::Gif => "gif",
+            MediaFormat::Mp4 => "mp4",
+            MediaFormat::Mov => "mov",
+            MediaFormat::Webm => "webm",
+            MediaFormat::Mkv => "mkv",
+            MediaFormat::Heic => "heic",
+            MediaFormat::Avif => "avif",
+        }
+    }
+
+    pub fn media_type(&self) -> MediaType {
+        match self {
+            MediaFormat::Jpeg | MediaFormat::Png | MediaFormat::Webp => MediaType::Photo,
+            MediaFormat::Gif => MediaType::Animation,
+            MediaFormat::Mp4 | MediaFormat::Mov => MediaType::Video,
+            MediaFormat::Webm | MediaFormat::Mkv | MediaFormat::Heic | MediaFormat::Avif => {
+                MediaType::Unsupported
+            }
+        }
+    }
+}
+
+pub fn format_from_mime(doc: &Document) -> Option<MediaFormat> {
+    match doc.mime_type.as_ref()?.to_string().as_str() {
+        "image/jpeg" => Some(MediaFormat::Jpeg),
+        "image/png" => Some(MediaFormat::Png),
+        "image/webp" => Some(MediaFormat::Webp),
+        "image/gif" => Some(MediaFormat::Gif),
+        "video/mp4" => Some(MediaFormat::Mp4),
+        "video/quicktime" => Some(MediaFormat::Mov),
+        "video/webm" => Some(MediaFormat::Webm),
+        "video/x-matroska" => Some(MediaFormat::Mkv),
+        "image/heic" | "image/heif" => Some(MediaFormat::Heic),
+        "image/avif" => Some(MediaFormat::Avif),
+        _ => None,
+    }
+}
+
+pub fn needs_sniffing(doc: &Document) -> bool {
+    match &doc.mime_type {
+        Some(mime) => mime.to_string() == "application/octet-stream",
+        None => true,
+    }
+}
+
+pub fn sniff_format(data: &[u8]) -> Option<MediaFormat> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(MediaFormat::Jpeg)
+    } else if data.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some(MediaFormat::Png)
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some(MediaFormat::Gif)
+    } else if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+        Some(MediaFormat::Webp)
+    } else if data.get(4..8) == Some(b"ftyp") {
+        match data.get(8..12)? {
+            b"heic" | b"heix" | b"hevc" | b"hevx" | b"mif1" | b"msf1" => Some(MediaFormat::Heic),
+            b"avif" | b"avis" => Some(MediaFormat::Avif),
+            b"qt  " => Some(MediaFormat::Mov),
+            _ => Some(MediaFormat::Mp4),
+        }
+    } else if data.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        let header = &data[..data.len().min(64)];
+        if header.windows(4).any(|window| window == b"webm") {
+            Some(MediaFormat::Webm)
+        } else {
+            Some(MediaFormat::Mkv)
+        }
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ftyp(brand: &[u8; 4]) -> Vec<u8> {
+        let mut header = vec![0x00, 0x00, 0x00, 0x20];
+        header.extend_from_slice(b"ftyp");
+        header.extend_from_slice(brand);
+        header.extend_from_slice(&[0x00, 0x00, 0x02, 0x00]);
+        header
+    }
+
+    fn ebml(doc_type: &[u8]) -> Vec<u8> {
+        let mut header = vec![0x1A, 0x45, 0xDF, 0xA3, 0x9F, 0x42, 0x86, 0x81, 0x01];
+        header.extend_from_slice(&[0x42, 0x82, 0x80 | doc_type.len() as u8]);
+        header.extend_from_slice(doc_type);
+        header
+    }
+
+    #[test]
+    fn sniffs_jpeg() {
+        let header = [0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F'];
+        assert_eq!(sniff_format(&header), Some(MediaFormat::Jpeg));
+    }
+
+    #[test]
+    fn sniffs_png() {
+        let header = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00];
+        assert_eq!(sniff_format(&header), Some(MediaFormat::Png));
+    }
+
+    #[test]
+    fn sniffs_gif() {
+        assert_eq!(sniff_format(b"GIF87a\x01\x00"), Some(MediaFormat::Gif));
+        assert_eq!(sniff_format(b"GIF89a\x01\x00"), Some(MediaFormat::Gif));
+    }
+
+    #[test]
+    fn sniffs_webp() {
+        assert_eq!(
+            sniff_format(b"RIFF\x24\x00\x00\x00WEBPVP8 "),
+            Some(MediaFormat::Webp)
+        );
+        assert_eq!(sniff_format(b"RIFF\x24\x00\x00\x00WAVEfmt "), None);
+    }
+
+    #[test]
+    fn sniffs_iso_media() {
+        assert_eq!(sniff_format(&ftyp(b"isom")), Some(MediaFormat::Mp4));
+        assert_eq!(sniff_format(&ftyp(b"mp42")), Some(MediaFormat::Mp4));
+        assert_eq!(sniff_format(&ftyp(b"qt  ")), Some(MediaFormat::Mov));
+        assert_eq!(sniff_format(&ftyp(b"heic")), Some(MediaFormat::Heic));
+        assert_eq!(sniff_format(&ftyp(b"mif1")), Some(MediaFormat::Heic));
+        assert_eq!(sniff_format(&ftyp(b"avif")), Some(MediaFormat::Avif));
+    }
+
+    #[test]
+    fn sniffs_matroska() {
+        assert_eq!(sniff_format(&ebml(b"webm")), Some(MediaFormat::Webm));
+        assert_eq!(sniff_format(&ebml(b"matroska")), Some(MediaFormat::Mkv));
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        assert_eq!(sniff_format(&[]), None);
+        assert_eq!(sniff_format(&[0xFF, 0xD8]), None);
+        assert_eq!(sniff_format(b"GIF8"), None);
+        assert_eq!(sniff_format(&[0x89, b'P', b'N', b'G']), None);
+        assert_eq!(sniff_format(b"RIFF\x24\x00"), None);
+        assert_eq!(sniff_format(&ftyp(b"isom")[..10]), None);
+    }
 }
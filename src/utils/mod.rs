@@ -1,10 +1,19 @@
+pub mod anon_utils;
+pub mod caption_utils;
+pub mod credit_utils;
 pub mod document_utils;
 pub mod env_utils;
 pub mod error_utils;
+pub mod html_utils;
+pub mod image_utils;
+pub mod link_utils;
 pub mod message_utils;
 pub mod mime_utils;
 pub mod option_utils;
 pub mod pic_utils;
+pub mod request_utils;
 pub mod result_utils;
+pub mod time_utils;
 pub mod user_utils;
 pub mod version;
+pub mod video_utils;
@@ -1,6 +1,7 @@
 use teloxide::prelude::*;
 
 use crate::utils::error_utils::HandlerError;
+use crate::utils::request_utils::SendRetry;
 
 pub async fn unwrap_send_error<T>(
     value: Option<T>,
@@ -8,8 +9,8 @@ pub async fn unwrap_send_error<T>(
     msg: &str,
 ) -> Result<T, HandlerError> {
     if value.is_none() {
-        cx.reply_to(msg).send().await?;
-        return Err(HandlerError::from_str(msg));
+        cx.reply_to(msg).send_retry().await?;
+        return Err(HandlerError::missing(msg));
     }
     Ok(value.unwrap())
 }
@@ -0,0 +1,79 @@
+use std::env;
+use std::time::Duration;
+
+use futures_util::future::BoxFuture;
+use lazy_static::lazy_static;
+use log::warn;
+use teloxide::requests::{Output, Request};
+use teloxide::RequestError;
+use tokio::time::sleep;
+
+static REQUEST_RETRIES_KEY: &str = "REQUEST_RETRIES";
+
+const BACKOFF_BASE: Duration = Duration::from_millis(500);
+const BACKOFF_MAX: Duration = Duration::from_secs(30);
+const RETRY_AFTER_MAX: u64 = 5 * 60;
+
+lazy_static! {
+    static ref REQUEST_RETRIES: u32 = env::var(REQUEST_RETRIES_KEY)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(3);
+}
+
+pub trait SendRetry: Request<Err = RequestError> {
+    fn send_retry(self) -> BoxFuture<'static, Result<Output<Self>, RequestError>>;
+
+    fn send_retry_unsent(self) -> BoxFuture<'static, Result<Output<Self>, RequestError>>;
+}
+
+impl<R> SendRetry for R
+where
+    R: Request<Err = RequestError> + Send + Sync + 'static,
+    R::SendRef: Send,
+    Output<R>: Send,
+{
+    fn send_retry(self) -> BoxFuture<'static, Result<Output<Self>, RequestError>> {
+        Box::pin(send_with_retries(self, true))
+    }
+
+    fn send_retry_unsent(self) -> BoxFuture<'static, Result<Output<Self>, RequestError>> {
+        Box::pin(send_with_retries(self, false))
+    }
+}
+
+async fn send_with_retries<R>(request: R, retry_delivered: bool) -> Result<Output<R>, RequestError>
+where
+    R: Request<Err = RequestError>,
+{
+    let mut attempt = 0;
+    loop {
+        let error = match request.send_ref().await {
+            Ok(output) => return Ok(output),
+            Err(error) => error,
+        };
+        let delay = match &error {
+            RequestError::RetryAfter(seconds) if (*seconds as u64) <= RETRY_AFTER_MAX => {
+                Duration::from_secs(*seconds as u64)
+            }
+            RequestError::NetworkError(e) if retry_delivered || e.is_connect() => backoff(attempt),
+            _ => return Err(error),
+        };
+        if attempt >= *REQUEST_RETRIES {
+            return Err(error);
+        }
+        attempt += 1;
+        warn!(
+            "Telegram request failed, retry {} in {:?}, due to error {:?}",
+            attempt, delay, error
+        );
+        sleep(delay).await;
+    }
+}
+
+fn backoff(attempt: u32) -> Duration {
+    BACKOFF_BASE
+        .checked_mul(1 << attempt.min(16))
+        .unwrap_or(BACKOFF_MAX)
+        .min(BACKOFF_MAX)
+}
@@ -0,0 +1,13 @@
+pub fn format_age(now: i64, created_at: i64) -> String {
+    if created_at <= 0 {
+        return "unknown age".to_string();
+    }
+    let hours = (now - created_at).max(0) / 3600;
+    if hours < 1 {
+        "<1h".to_string()
+    } else if hours < 48 {
+        format!("{}h", hours)
+    } else {
+        format!("{}d", hours / 24)
+    }
+}
@@ -2,6 +2,7 @@ use teloxide::types::User;
 
 pub(crate) trait ExtUser {
     fn ftm_title(&self) -> String;
+    fn credit_title(&self) -> String;
 }
 
 impl ExtUser for User {
@@ -20,4 +21,18 @@ impl ExtUser for User {
             .collect::<Vec<String>>()
             .join(" ");
     }
+
+    fn credit_title(&self) -> String {
+        if let Some(username) = &self.username {
+            return format!("@{}", username);
+        }
+        let empty = &String::new();
+        let _last_name = self.last_name.as_ref().unwrap_or(empty);
+        return [&self.first_name, _last_name]
+            .iter()
+            .filter(|item| !item.is_empty())
+            .map(|item| item.to_string())
+            .collect::<Vec<String>>()
+            .join(" ");
+    }
 }
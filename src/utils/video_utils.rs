@@ -0,0 +1,65 @@
+use std::env;
+use std::path::Path;
+use std::process::Stdio;
+
+use lazy_static::lazy_static;
+use log::warn;
+use tokio::process::Command;
+
+use crate::utils::document_utils::TempFile;
+
+static GIF_TO_MP4_KEY: &str = "GIF_TO_MP4";
+static GIF_TO_MP4_MIN_SIZE_KEY: &str = "GIF_TO_MP4_MIN_SIZE";
+static FFMPEG_PATH_KEY: &str = "FFMPEG_PATH";
+
+lazy_static! {
+    static ref GIF_TO_MP4: bool = env::var(GIF_TO_MP4_KEY)
+        .map(|value| value == "true")
+        .unwrap_or(false);
+    static ref GIF_TO_MP4_MIN_SIZE: u32 = env::var(GIF_TO_MP4_MIN_SIZE_KEY)
+        .ok()
+        .and_then(|value| value.parse::<u32>().ok())
+        .unwrap_or(1024)
+        * 1024;
+    static ref FFMPEG_PATH: String =
+        env::var(FFMPEG_PATH_KEY).unwrap_or_else(|_| "ffmpeg".to_string());
+}
+
+pub fn should_transcode_gif(size: u32) -> bool {
+    *GIF_TO_MP4 && size >= *GIF_TO_MP4_MIN_SIZE
+}
+
+pub async fn gif_to_mp4(input: &Path) -> Option<TempFile> {
+    let output = TempFile {
+        path: input.with_extension("mp4"),
+    };
+    let status = Command::new(FFMPEG_PATH.as_str())
+        .arg("-y")
+        .args(&["-loglevel", "error"])
+        .arg("-i")
+        .arg(input)
+        .args(&["-movflags", "faststart"])
+        .args(&["-pix_fmt", "yuv420p"])
+        .args(&["-vf", "scale=trunc(iw/2)*2:trunc(ih/2)*2"])
+        .arg("-an")
+        .arg(&output.path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await;
+    match status {
+        Ok(status) if status.success() => Some(output),
+        Ok(status) => {
+            warn!("Can not convert gif to mp4, ffmpeg exited with {}", status);
+            None
+        }
+        Err(e) => {
+            warn!(
+                "Can not run ffmpeg at {}, due to error {:?}",
+                *FFMPEG_PATH, e
+            );
+            None
+        }
+    }
+}
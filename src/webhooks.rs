@@ -0,0 +1,198 @@
+use std::env;
+use std::fs;
+use std::time::Duration;
+
+use chrono::Utc;
+use hmac::{Hmac, Mac, NewMac};
+use lazy_static::lazy_static;
+use log::warn;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use tokio::sync::Notify;
+use tokio::time::sleep;
+
+use crate::data::model::webhook_event::WebhookEvent;
+use crate::data::repo::webhook_event_repo::WebhookEventRepo;
+use crate::utils::error_utils::HandlerError;
+use crate::utils::result_utils::FatalValueMapper;
+
+static WEBHOOKS_FILE_KEY: &str = "WEBHOOKS_FILE";
+
+static SIGNATURE_HEADER: &str = "X-Signature-256";
+static EVENT_HEADER: &str = "X-Event";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+const BATCH_SIZE: i64 = 50;
+const MAX_ATTEMPTS: i32 = 10;
+const MAX_BACKOFF_SECS: i64 = 60 * 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventKind {
+    SuggestionReceived,
+    SuggestionAccepted,
+    SuggestionDeclined,
+    UserBanned,
+    UserUnbanned,
+    PicAdded,
+    PicRemoved,
+}
+
+impl WebhookEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEventKind::SuggestionReceived => "suggestion_received",
+            WebhookEventKind::SuggestionAccepted => "suggestion_accepted",
+            WebhookEventKind::SuggestionDeclined => "suggestion_declined",
+            WebhookEventKind::UserBanned => "user_banned",
+            WebhookEventKind::UserUnbanned => "user_unbanned",
+            WebhookEventKind::PicAdded => "pic_added",
+            WebhookEventKind::PicRemoved => "pic_removed",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Webhook {
+    url: String,
+    secret: String,
+    #[serde(default)]
+    events: Vec<WebhookEventKind>,
+}
+
+#[derive(Deserialize)]
+struct WebhooksConfig {
+    webhooks: Vec<Webhook>,
+}
+
+lazy_static! {
+    static ref WEBHOOKS: Vec<Webhook> = load_webhooks();
+    static ref WAKE_UP: Notify = Notify::new();
+    static ref CLIENT: Client = Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap_or_default();
+}
+
+fn load_webhooks() -> Vec<Webhook> {
+    let path = match env::var(WEBHOOKS_FILE_KEY) {
+        Ok(path) => path,
+        Err(_) => return Vec::new(),
+    };
+    let content =
+        fs::read_to_string(&path).map_value_or_exit(format!("Can not read webhooks file {}", path));
+    serde_json::from_str::<WebhooksConfig>(&content)
+        .map_value_or_exit(format!("Can not parse webhooks file {}", path))
+        .webhooks
+}
+
+pub async fn emit(repo: &WebhookEventRepo, kind: WebhookEventKind, data: Value) {
+    let now = Utc::now().timestamp();
+    let payload = json!({
+        "event": kind.as_str(),
+        "timestamp": now,
+        "data": data,
+    })
+    .to_string();
+    let mut queued = false;
+    for webhook in WEBHOOKS
+        .iter()
+        .filter(|webhook| webhook.events.is_empty() || webhook.events.contains(&kind))
+    {
+        match repo
+            .enqueue(&webhook.url, kind.as_str(), &payload, now)
+            .await
+        {
+            Ok(_) => queued = true,
+            Err(e) => warn!(
+                "Can not queue {} webhook for {}, due to error {:?}",
+                kind.as_str(),
+                webhook.url,
+                e
+            ),
+        }
+    }
+    if queued {
+        WAKE_UP.notify_one();
+    }
+}
+
+pub fn start_webhook_dispatcher(repo: WebhookEventRepo) {
+    if WEBHOOKS.is_empty() {
+        return;
+    }
+    tokio::spawn(async move {
+        loop {
+            if let Err(err) = deliver_due(&repo).await {
+                warn!("{}", err);
+            }
+            tokio::select! {
+                _ = WAKE_UP.notified() => {}
+                _ = sleep(POLL_INTERVAL) => {}
+            }
+        }
+    });
+}
+
+async fn deliver_due(repo: &WebhookEventRepo) -> Result<(), HandlerError> {
+    let events = repo.get_due(Utc::now().timestamp(), BATCH_SIZE).await?;
+    for event in events {
+        match deliver(&event).await {
+            Ok(_) => repo.delete(event.id).await?,
+            Err(e) if event.attempts + 1 >= MAX_ATTEMPTS => {
+                warn!(
+                    "Dropping {} webhook for {} after {} attempts, due to error {:?}",
+                    event.event,
+                    event.url,
+                    event.attempts + 1,
+                    e
+                );
+                repo.delete(event.id).await?
+            }
+            Err(e) => {
+                warn!(
+                    "Can not deliver {} webhook to {}, due to error {:?}",
+                    event.event, event.url, e
+                );
+                let backoff = (30i64 << event.attempts.min(16)).min(MAX_BACKOFF_SECS);
+                repo.reschedule(
+                    event.id,
+                    event.attempts + 1,
+                    Utc::now().timestamp() + backoff,
+                )
+                .await?
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn deliver(event: &WebhookEvent) -> Result<(), HandlerError> {
+    let webhook = match WEBHOOKS.iter().find(|webhook| webhook.url == event.url) {
+        Some(webhook) => webhook,
+        None => return Ok(()),
+    };
+    CLIENT
+        .post(&event.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, &event.event)
+        .header(
+            SIGNATURE_HEADER,
+            format!("sha256={}", sign(&webhook.secret, &event.payload)),
+        )
+        .body(event.payload.to_string())
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| HandlerError::External(format!("Webhook error: {:?}", e)))?;
+    Ok(())
+}
+
+fn sign(secret: &str, payload: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(payload.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}